@@ -1,6 +1,9 @@
 use bevy::{audio::Volume, prelude::*};
 
-use crate::player::{GoalEvent, JumpEvent, SwitchEvent};
+use crate::{
+    links::KeyCollectedEvent,
+    player::{DeathEvent, GoalEvent, JumpEvent, SwitchBlockedEvent, SwitchEvent},
+};
 
 pub struct AudioPlugin;
 
@@ -10,14 +13,27 @@ struct JumpSound(Handle<AudioSource>);
 #[derive(Resource)]
 struct SwitchSound(Handle<AudioSource>);
 
+#[derive(Resource)]
+struct SwitchBlockedSound(Handle<AudioSource>);
+
 #[derive(Resource)]
 struct GoalSound(Handle<AudioSource>);
 
+#[derive(Resource)]
+struct DeathSound(Handle<AudioSource>);
+
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup).add_systems(
             FixedUpdate,
-            (play_jump_sound, play_switch_sound, play_goal_sound),
+            (
+                play_jump_sound,
+                play_switch_sound,
+                play_switch_blocked_sound,
+                play_goal_sound,
+                play_death_sound,
+                play_key_sound,
+            ),
         );
     }
 }
@@ -34,8 +50,14 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     let switch_sound = asset_server.load("sounds/switch.ogg");
     commands.insert_resource(SwitchSound(switch_sound));
 
+    let switch_blocked_sound = asset_server.load("sounds/blocked.ogg");
+    commands.insert_resource(SwitchBlockedSound(switch_blocked_sound));
+
     let goal_sound = asset_server.load("sounds/goal.ogg");
     commands.insert_resource(GoalSound(goal_sound));
+
+    let death_sound = asset_server.load("sounds/death.ogg");
+    commands.insert_resource(DeathSound(death_sound));
 }
 
 fn play_jump_sound(
@@ -43,7 +65,7 @@ fn play_jump_sound(
     jump_event_reader: EventReader<JumpEvent>,
     jump_sound: Res<JumpSound>,
 ) {
-    play_sound_effect(commands, jump_event_reader, jump_sound.0.clone(), 0.5);
+    play_sound_effect(commands, jump_event_reader, jump_sound.0.clone(), 0.5, 1.0);
 }
 
 fn play_switch_sound(
@@ -51,7 +73,27 @@ fn play_switch_sound(
     switch_event_reader: EventReader<SwitchEvent>,
     switch_sound: Res<SwitchSound>,
 ) {
-    play_sound_effect(commands, switch_event_reader, switch_sound.0.clone(), 0.5);
+    play_sound_effect(
+        commands,
+        switch_event_reader,
+        switch_sound.0.clone(),
+        0.5,
+        1.0,
+    );
+}
+
+fn play_switch_blocked_sound(
+    commands: Commands,
+    blocked_event_reader: EventReader<SwitchBlockedEvent>,
+    switch_blocked_sound: Res<SwitchBlockedSound>,
+) {
+    play_sound_effect(
+        commands,
+        blocked_event_reader,
+        switch_blocked_sound.0.clone(),
+        0.5,
+        1.0,
+    );
 }
 
 fn play_goal_sound(
@@ -59,7 +101,30 @@ fn play_goal_sound(
     goal_event_reader: EventReader<GoalEvent>,
     goal_sound: Res<GoalSound>,
 ) {
-    play_sound_effect(commands, goal_event_reader, goal_sound.0.clone(), 0.5);
+    play_sound_effect(commands, goal_event_reader, goal_sound.0.clone(), 0.5, 1.0);
+}
+
+// The goal sound played quick and high
+fn play_key_sound(
+    commands: Commands,
+    key_event_reader: EventReader<KeyCollectedEvent>,
+    goal_sound: Res<GoalSound>,
+) {
+    play_sound_effect(commands, key_event_reader, goal_sound.0.clone(), 0.4, 1.5);
+}
+
+fn play_death_sound(
+    commands: Commands,
+    death_event_reader: EventReader<DeathEvent>,
+    death_sound: Res<DeathSound>,
+) {
+    play_sound_effect(
+        commands,
+        death_event_reader,
+        death_sound.0.clone(),
+        0.6,
+        1.0,
+    );
 }
 
 fn play_sound_effect<T: Event>(
@@ -67,13 +132,16 @@ fn play_sound_effect<T: Event>(
     mut event_reader: EventReader<T>,
     source: Handle<AudioSource>,
     volume: f32,
+    speed: f32,
 ) {
     if !event_reader.is_empty() {
         event_reader.clear();
 
         commands.spawn(AudioBundle {
             source,
-            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(volume))
+                .with_speed(speed),
         });
     }
 }
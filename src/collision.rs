@@ -1,12 +1,42 @@
-use bevy::{math::bounding::Aabb2d, prelude::*};
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
+    prelude::*,
+    transform::TransformSystem,
+    utils::HashMap,
+};
+use bitflags::bitflags;
+use serde::Deserialize;
+
+use crate::{level::LevelState, movement::MovementConfig};
+
+// Matches the tilemap's TILE_SIZE so most colliders sit in one cell
+const CELL_SIZE: f32 = 16.0;
+
+// Gaps and overlaps smaller than this count as touching, to absorb float error
+const CONTACT_EPSILON: f32 = 0.01;
+
+bitflags! {
+    // Written by name in asset files, e.g. `"BLUE | YELLOW"`
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+    #[serde(transparent)]
+    pub struct CollisionLayers: u32 {
+        const NEUTRAL = 1 << 0;
+        const BLUE = 1 << 1;
+        const YELLOW = 1 << 2;
+    }
+}
 
 #[derive(Component, Debug, Default)]
 pub struct Collider {
     pub width: f32,
     pub height: f32,
-    // Bitmask?
-    pub layer_mask: u8,
+    // Layers this collider is part of
+    pub membership: CollisionLayers,
+    // Layers this collider is stopped by or detects
+    pub filter: CollisionLayers,
     pub is_trigger: bool,
+    // Only stops bodies landing on it from above
+    pub one_way: bool,
 }
 
 impl Collider {
@@ -14,3 +44,714 @@ impl Collider {
         Aabb2d::new(center, Vec2::new(self.width / 2.0, self.height / 2.0))
     }
 }
+
+// Sides of a body that were stopped by something during its last move.
+// Walls only register while moving into them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContactSet {
+    pub grounded: bool,
+    // What was stood on, set along with grounded
+    pub ground: Option<Entity>,
+    pub ceiling: bool,
+    pub wall_left: bool,
+    pub wall_right: bool,
+}
+
+impl ContactSet {
+    pub fn on_wall(&self) -> bool {
+        self.wall_left || self.wall_right
+    }
+}
+
+// Moved by the physics systems and stopped by solid colliders sharing a layer.
+// Velocity is in pixels per second, gravity comes from MovementConfig
+#[derive(Component, Debug)]
+pub struct KinematicBody {
+    pub velocity: Vec2,
+    pub gravity_scale: f32,
+    pub contacts: ContactSet,
+    // Falls through one-way platforms while set
+    pub drop_through: bool,
+}
+
+impl Default for KinematicBody {
+    fn default() -> Self {
+        KinematicBody {
+            velocity: Vec2::ZERO,
+            gravity_scale: 1.0,
+            contacts: ContactSet::default(),
+            drop_through: false,
+        }
+    }
+}
+
+// A solid collider that moves itself in PhysicsSet, before bodies move, carrying
+// any body standing on it. Velocity is in pixels per second
+#[derive(Component, Debug, Default)]
+pub struct MovingCollider {
+    pub velocity: Vec2,
+}
+
+// Fraction of the displacement travelled before touching, and the surface normal there
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    pub time: f32,
+    pub normal: Vec2,
+}
+
+// Slab test of the moving box's center against the target grown by the moving box's size.
+// Boxes that already overlap are ignored so bodies can always move out of them
+pub fn sweep_aabb(moving: Aabb2d, displacement: Vec2, target: Aabb2d) -> Option<SweepHit> {
+    let origin = moving.center();
+    let min = target.min - moving.half_size();
+    let max = target.max + moving.half_size();
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for axis in 0..2 {
+        let (o, d, lo, hi) = (origin[axis], displacement[axis], min[axis], max[axis]);
+
+        // Not moving on this axis, so we have to already be between the faces
+        if d == 0.0 {
+            if o <= lo + CONTACT_EPSILON || o >= hi - CONTACT_EPSILON {
+                return None;
+            }
+            continue;
+        }
+
+        // Distances to the face we would enter through and the one we would leave through
+        let (near, far) = if d > 0.0 {
+            (lo - o, hi - o)
+        } else {
+            (o - hi, o - lo)
+        };
+
+        let axis_entry = if near < -CONTACT_EPSILON {
+            f32::NEG_INFINITY
+        } else {
+            near.max(0.0) / d.abs()
+        };
+
+        if axis_entry > entry {
+            entry = axis_entry;
+            normal = Vec2::ZERO;
+            normal[axis] = -d.signum();
+        }
+        exit = exit.min(far / d.abs());
+    }
+
+    if entry == f32::NEG_INFINITY || entry > 1.0 || entry >= exit {
+        return None;
+    }
+
+    Some(SweepHit {
+        time: entry,
+        normal,
+    })
+}
+
+// Earliest hit against any of the targets
+pub fn sweep_aabb_all<'a>(
+    moving: Aabb2d,
+    displacement: Vec2,
+    targets: impl IntoIterator<Item = &'a Aabb2d>,
+) -> Option<SweepHit> {
+    targets
+        .into_iter()
+        .filter_map(|target| sweep_aabb(moving, displacement, *target))
+        .min_by(|a, b| a.time.total_cmp(&b.time))
+}
+
+// Box covering everything a collider passes through on its way to the destination
+pub fn swept_aabb(moving: Aabb2d, displacement: Vec2) -> Aabb2d {
+    moving.merge(&Aabb2d::new(
+        moving.center() + displacement,
+        moving.half_size(),
+    ))
+}
+
+// Copy of a collider as of the last CollisionWorld update
+#[derive(Clone, Copy, Debug)]
+pub struct CollisionEntry {
+    pub entity: Entity,
+    pub aabb: Aabb2d,
+    pub membership: CollisionLayers,
+    pub filter: CollisionLayers,
+    pub is_trigger: bool,
+    pub one_way: bool,
+    min_cell: IVec2,
+    max_cell: IVec2,
+}
+
+// Uniform grid broadphase over every Collider, so queries only look at nearby ones
+#[derive(Resource, Default)]
+pub struct CollisionWorld {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    entries: HashMap<Entity, CollisionEntry>,
+}
+
+impl CollisionWorld {
+    // Colliders that are members of any of the filter's layers
+    pub fn query_aabb(&self, aabb: Aabb2d, filter: CollisionLayers) -> Vec<CollisionEntry> {
+        let (min_cell, max_cell) = cell_range(aabb);
+        let mut seen = vec![];
+
+        for y in min_cell.y..=max_cell.y {
+            for x in min_cell.x..=max_cell.x {
+                let Some(entities) = self.cells.get(&IVec2::new(x, y)) else {
+                    continue;
+                };
+
+                for entity in entities {
+                    let entry = &self.entries[entity];
+                    if entry.membership.intersects(filter)
+                        && entry.aabb.intersects(&aabb)
+                        && !seen.iter().any(|e: &CollisionEntry| e.entity == *entity)
+                    {
+                        seen.push(*entry);
+                    }
+                }
+            }
+        }
+
+        seen
+    }
+
+    // Solid colliders the box is inside of, rather than just touching.
+    // Being inside a one-way platform is fine, bodies just pass up through it
+    pub fn overlapping(
+        &self,
+        entity: Entity,
+        aabb: Aabb2d,
+        filter: CollisionLayers,
+    ) -> Vec<CollisionEntry> {
+        self.query_aabb(aabb.shrink(Vec2::splat(CONTACT_EPSILON)), filter)
+            .into_iter()
+            .filter(|entry| entry.entity != entity && !entry.is_trigger && !entry.one_way)
+            .collect()
+    }
+
+    // Whether two colliders touch as of the last update, the same test contacts use
+    pub fn touching(&self, entity: Entity, other: Entity) -> bool {
+        match (self.entries.get(&entity), self.entries.get(&other)) {
+            (Some(entry), Some(other)) => entry
+                .aabb
+                .grow(Vec2::splat(CONTACT_EPSILON))
+                .intersects(&other.aabb),
+            _ => false,
+        }
+    }
+
+    // Shortest move that leaves the box clear of everything in the filter, trying each
+    // side of each collider it is inside of. None if no single push gets it clear
+    pub fn push_out(&self, entity: Entity, aabb: Aabb2d, filter: CollisionLayers) -> Option<Vec2> {
+        let mut pushes = self
+            .overlapping(entity, aabb, filter)
+            .iter()
+            .flat_map(|other| {
+                [
+                    Vec2::new(other.aabb.max.x - aabb.min.x, 0.0),
+                    Vec2::new(other.aabb.min.x - aabb.max.x, 0.0),
+                    Vec2::new(0.0, other.aabb.max.y - aabb.min.y),
+                    Vec2::new(0.0, other.aabb.min.y - aabb.max.y),
+                ]
+            })
+            .collect::<Vec<Vec2>>();
+        pushes.sort_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+        pushes.into_iter().find(|push| {
+            let pushed = Aabb2d::new(aabb.center() + *push, aabb.half_size());
+            self.overlapping(entity, pushed, filter).is_empty()
+        })
+    }
+
+    // Public so tests can seed a world without running the collision systems
+    pub fn insert(&mut self, entity: Entity, aabb: Aabb2d, collider: &Collider) {
+        self.remove(entity);
+
+        let (min_cell, max_cell) = cell_range(aabb);
+        for y in min_cell.y..=max_cell.y {
+            for x in min_cell.x..=max_cell.x {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+
+        self.entries.insert(
+            entity,
+            CollisionEntry {
+                entity,
+                aabb,
+                membership: collider.membership,
+                filter: collider.filter,
+                is_trigger: collider.is_trigger,
+                one_way: collider.one_way,
+                min_cell,
+                max_cell,
+            },
+        );
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(entry) = self.entries.remove(&entity) else {
+            return;
+        };
+
+        for y in entry.min_cell.y..=entry.max_cell.y {
+            for x in entry.min_cell.x..=entry.max_cell.x {
+                let cell = IVec2::new(x, y);
+                if let Some(entities) = self.cells.get_mut(&cell) {
+                    entities.retain(|&e| e != entity);
+                    if entities.is_empty() {
+                        self.cells.remove(&cell);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn cell(point: Vec2) -> IVec2 {
+    (point / CELL_SIZE).floor().as_ivec2()
+}
+
+fn cell_range(aabb: Aabb2d) -> (IVec2, IVec2) {
+    (cell(aabb.min), cell(aabb.max))
+}
+
+// Sent for colliders with a filter, with `other` being what they touched.
+// The normal is on other's surface, pointing back towards entity
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionStarted {
+    pub entity: Entity,
+    pub other: Entity,
+    pub normal: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionEnded {
+    pub entity: Entity,
+    pub other: Entity,
+    pub normal: Vec2,
+}
+
+// As above, when either collider is a trigger
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TriggerEntered {
+    pub entity: Entity,
+    pub other: Entity,
+    pub normal: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TriggerExited {
+    pub entity: Entity,
+    pub other: Entity,
+    pub normal: Vec2,
+}
+
+#[derive(Clone, Copy)]
+struct Contact {
+    normal: Vec2,
+    is_trigger: bool,
+}
+
+// Contacts as of the last update, keyed by (entity, other)
+#[derive(Resource, Default)]
+struct Contacts(HashMap<(Entity, Entity), Contact>);
+
+// CollisionWorld is up to date once this has run in PostUpdate
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollisionWorldSet;
+
+// Moves every KinematicBody in FixedUpdate, set velocities before and read results after
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhysicsSet;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<TriggerEntered>()
+            .add_event::<TriggerExited>()
+            .insert_resource(CollisionWorld::default())
+            .insert_resource(Contacts::default())
+            .configure_sets(FixedUpdate, PhysicsSet.run_if(in_state(LevelState::Play)))
+            .add_systems(
+                FixedUpdate,
+                (move_colliders, move_bodies).chain().in_set(PhysicsSet),
+            )
+            .add_systems(Update, log_contacts)
+            .add_systems(
+                PostUpdate,
+                (update_collision_world, update_contacts)
+                    .chain()
+                    .in_set(CollisionWorldSet)
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+type ColliderChanged = Or<(Changed<GlobalTransform>, Changed<Collider>)>;
+
+fn update_collision_world(
+    mut collision_world: ResMut<CollisionWorld>,
+    collider_query: Query<(Entity, &GlobalTransform, &Collider), ColliderChanged>,
+    mut removed_colliders: RemovedComponents<Collider>,
+) {
+    for entity in removed_colliders.read() {
+        collision_world.remove(entity);
+    }
+
+    for (entity, transform, collider) in &collider_query {
+        let aabb = collider.get_aabb2d(transform.translation().truncate());
+        collision_world.insert(entity, aabb, collider);
+    }
+}
+
+// Moves along y then x, stopping flush against the first collider on each axis.
+// Returns how far the box actually moved and which of its sides were stopped
+fn resolve_movement(
+    aabb: Aabb2d,
+    displacement: Vec2,
+    colliders: &[CollisionEntry],
+    drop_through: bool,
+) -> (Vec2, ContactSet) {
+    let mut contacts = ContactSet::default();
+    let mut moved = displacement;
+
+    // One-way platforms only count when coming down from fully above them.
+    // Kept with their entity so we know what we landed on
+    let y_hit = colliders
+        .iter()
+        .filter(|entry| {
+            !entry.one_way
+                || (!drop_through
+                    && displacement.y < 0.0
+                    && aabb.min.y >= entry.aabb.max.y - CONTACT_EPSILON)
+        })
+        .filter_map(|entry| {
+            sweep_aabb(aabb, Vec2::new(0.0, displacement.y), entry.aabb)
+                .map(|hit| (hit, entry.entity))
+        })
+        .min_by(|(a, _), (b, _)| a.time.total_cmp(&b.time));
+    let x_colliders = colliders
+        .iter()
+        .filter(|entry| !entry.one_way)
+        .map(|entry| &entry.aabb);
+
+    if let Some((hit, entity)) = y_hit {
+        moved.y *= hit.time;
+        contacts.grounded = hit.normal.y > 0.0;
+        contacts.ceiling = hit.normal.y < 0.0;
+        contacts.ground = contacts.grounded.then_some(entity);
+    }
+
+    let aabb = Aabb2d::new(aabb.center() + Vec2::new(0.0, moved.y), aabb.half_size());
+    if let Some(hit) = sweep_aabb_all(aabb, Vec2::new(displacement.x, 0.0), x_colliders) {
+        moved.x *= hit.time;
+        contacts.wall_left = hit.normal.x > 0.0;
+        contacts.wall_right = hit.normal.x < 0.0;
+    }
+
+    (moved, contacts)
+}
+
+// Riders are moved the same amount first, so they are still standing on the
+// collider when move_bodies runs. Stops them at walls like any other move.
+// Bodies above that a rising collider's top passes this tick are lifted onto it
+fn move_colliders(
+    time: Res<Time>,
+    mut collision_world: ResMut<CollisionWorld>,
+    mut collider_query: Query<(Entity, &MovingCollider, &mut Transform, &Collider)>,
+    mut body_query: Query<
+        (Entity, &KinematicBody, &mut Transform, &Collider),
+        Without<MovingCollider>,
+    >,
+) {
+    let delta = time.delta_seconds();
+
+    for (collider_entity, moving, mut transform, collider) in &mut collider_query {
+        let displacement = moving.velocity * delta;
+        if displacement == Vec2::ZERO {
+            continue;
+        }
+
+        let old_top = collider.get_aabb2d(transform.translation.truncate()).max.y;
+        transform.translation += displacement.extend(0.0);
+        let bounding_box = collider.get_aabb2d(transform.translation.truncate());
+        collision_world.insert(collider_entity, bounding_box, collider);
+
+        for (entity, body, mut body_transform, body_collider) in &mut body_query {
+            // Riders that switched away from its layer fall through instead
+            if !collider.membership.intersects(body_collider.filter)
+                || (collider.one_way && body.drop_through)
+            {
+                continue;
+            }
+
+            let body_box = body_collider.get_aabb2d(body_transform.translation.truncate());
+            let overtaken = body_box.min.y >= old_top - CONTACT_EPSILON
+                && body_box.min.y < bounding_box.max.y
+                && body_box.min.x < bounding_box.max.x
+                && body_box.max.x > bounding_box.min.x;
+
+            let displacement = if body.contacts.ground == Some(collider_entity) {
+                displacement
+            } else if overtaken {
+                Vec2::new(0.0, bounding_box.max.y - body_box.min.y)
+            } else {
+                continue;
+            };
+            let close_colliders = collision_world
+                .query_aabb(swept_aabb(body_box, displacement), body_collider.filter)
+                .into_iter()
+                .filter(|entry| {
+                    entry.entity != entity && entry.entity != collider_entity && !entry.is_trigger
+                })
+                .collect::<Vec<CollisionEntry>>();
+
+            let (moved, _) =
+                resolve_movement(body_box, displacement, &close_colliders, body.drop_through);
+            body_transform.translation += moved.extend(0.0);
+
+            let body_box = body_collider.get_aabb2d(body_transform.translation.truncate());
+            collision_world.insert(entity, body_box, body_collider);
+        }
+    }
+}
+
+fn move_bodies(
+    time: Res<Time>,
+    movement_config: Res<MovementConfig>,
+    mut collision_world: ResMut<CollisionWorld>,
+    mut body_query: Query<(Entity, &mut KinematicBody, &mut Transform, &Collider)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut body, mut transform, collider) in &mut body_query {
+        let bounding_box = collider.get_aabb2d(transform.translation.truncate());
+        let displacement = body.velocity * delta;
+
+        // Everything along the whole path, so fast moves can't skip thin tiles
+        let close_colliders = collision_world
+            .query_aabb(swept_aabb(bounding_box, displacement), collider.filter)
+            .into_iter()
+            .filter(|entry| entry.entity != entity && !entry.is_trigger)
+            .collect::<Vec<CollisionEntry>>();
+
+        let (moved, contacts) = resolve_movement(
+            bounding_box,
+            displacement,
+            &close_colliders,
+            body.drop_through,
+        );
+        transform.translation += moved.extend(0.0);
+        body.contacts = contacts;
+
+        if contacts.grounded || contacts.ceiling {
+            body.velocity.y = 0.0;
+        }
+        if contacts.on_wall() {
+            body.velocity.x = 0.0;
+        }
+
+        // Applied after moving so velocity set this tick is used as is
+        body.velocity.y -= movement_config.gravity * body.gravity_scale * delta;
+        body.velocity.y = body.velocity.y.max(-movement_config.terminal_velocity);
+
+        // Later bodies and ticks in this frame should see where we ended up
+        let bounding_box = collider.get_aabb2d(transform.translation.truncate());
+        collision_world.insert(entity, bounding_box, collider);
+    }
+}
+
+// Which way to push entity out of other, along the axis they overlap least on
+fn contact_normal(aabb: Aabb2d, other: Aabb2d) -> Vec2 {
+    let overlap = aabb.max.min(other.max) - aabb.min.max(other.min);
+    let offset = aabb.center() - other.center();
+
+    if overlap.x < overlap.y {
+        Vec2::new(offset.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, offset.y.signum())
+    }
+}
+
+fn update_contacts(
+    collision_world: Res<CollisionWorld>,
+    mut contacts: ResMut<Contacts>,
+    mut started_event_writer: EventWriter<CollisionStarted>,
+    mut ended_event_writer: EventWriter<CollisionEnded>,
+    mut entered_event_writer: EventWriter<TriggerEntered>,
+    mut exited_event_writer: EventWriter<TriggerExited>,
+) {
+    let mut current = HashMap::new();
+
+    for entry in collision_world.entries.values() {
+        if entry.filter.is_empty() {
+            continue;
+        }
+
+        // Grown slightly so resting flush against a surface counts as touching it
+        let touch_box = entry.aabb.grow(Vec2::splat(CONTACT_EPSILON));
+
+        for other in collision_world.query_aabb(touch_box, entry.filter) {
+            if other.entity == entry.entity {
+                continue;
+            }
+
+            current.insert(
+                (entry.entity, other.entity),
+                Contact {
+                    normal: contact_normal(entry.aabb, other.aabb),
+                    is_trigger: entry.is_trigger || other.is_trigger,
+                },
+            );
+        }
+    }
+
+    for (&(entity, other), contact) in &current {
+        if contacts.0.contains_key(&(entity, other)) {
+            continue;
+        }
+
+        let normal = contact.normal;
+        if contact.is_trigger {
+            entered_event_writer.send(TriggerEntered {
+                entity,
+                other,
+                normal,
+            });
+        } else {
+            started_event_writer.send(CollisionStarted {
+                entity,
+                other,
+                normal,
+            });
+        }
+    }
+
+    // Includes contacts with colliders that were despawned
+    for (&(entity, other), contact) in &contacts.0 {
+        if current.contains_key(&(entity, other)) {
+            continue;
+        }
+
+        let normal = contact.normal;
+        if contact.is_trigger {
+            exited_event_writer.send(TriggerExited {
+                entity,
+                other,
+                normal,
+            });
+        } else {
+            ended_event_writer.send(CollisionEnded {
+                entity,
+                other,
+                normal,
+            });
+        }
+    }
+
+    contacts.0 = current;
+}
+
+// Shown with RUST_LOG=passthrough::collision=debug
+fn log_contacts(
+    mut started_event_reader: EventReader<CollisionStarted>,
+    mut ended_event_reader: EventReader<CollisionEnded>,
+    mut entered_event_reader: EventReader<TriggerEntered>,
+    mut exited_event_reader: EventReader<TriggerExited>,
+) {
+    for event in started_event_reader.read() {
+        debug!(
+            "{:?} started touching {:?}, normal {}",
+            event.entity, event.other, event.normal
+        );
+    }
+
+    for event in ended_event_reader.read() {
+        debug!(
+            "{:?} stopped touching {:?}, normal {}",
+            event.entity, event.other, event.normal
+        );
+    }
+
+    for event in entered_event_reader.read() {
+        debug!(
+            "{:?} entered trigger {:?}, normal {}",
+            event.entity, event.other, event.normal
+        );
+    }
+
+    for event in exited_event_reader.read() {
+        debug!(
+            "{:?} exited trigger {:?}, normal {}",
+            event.entity, event.other, event.normal
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32) -> Aabb2d {
+        Aabb2d::new(Vec2::new(x, y), Vec2::splat(4.0))
+    }
+
+    #[test]
+    fn hits_at_time_of_impact() {
+        let hit = sweep_aabb(square(0.0, 0.0), Vec2::new(20.0, 0.0), square(16.0, 0.0));
+
+        assert_eq!(
+            hit,
+            Some(SweepHit {
+                time: 0.4,
+                normal: Vec2::NEG_X,
+            })
+        );
+        assert_eq!(
+            sweep_aabb(square(0.0, 0.0), Vec2::new(5.0, 0.0), square(16.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn hits_at_time_zero_when_flush() {
+        let hit = sweep_aabb(square(0.0, 8.0), Vec2::new(0.0, -5.0), square(0.0, 0.0));
+
+        assert_eq!(
+            hit,
+            Some(SweepHit {
+                time: 0.0,
+                normal: Vec2::Y,
+            })
+        );
+    }
+
+    #[test]
+    fn slides_along_a_wall_without_a_hit() {
+        let wall = square(8.0, 0.0);
+
+        assert_eq!(
+            sweep_aabb(square(0.0, 0.0), Vec2::new(0.0, 10.0), wall),
+            None
+        );
+        assert_eq!(
+            sweep_aabb(square(0.0, 0.0), Vec2::new(0.0, -10.0), wall),
+            None
+        );
+    }
+
+    #[test]
+    fn ignores_boxes_already_overlapping() {
+        let hit = sweep_aabb(square(0.0, 0.0), Vec2::new(5.0, 0.0), square(2.0, 0.0));
+
+        assert_eq!(hit, None);
+    }
+}
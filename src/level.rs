@@ -1,19 +1,29 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::collision::CollisionLayers;
 
 const FADE_DURATION: f32 = 0.5;
-const LEVEL_COUNT: u8 = 3;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum LevelState {
     #[default]
+    Loading,
     Init,
     Play,
     End,
+    // Level files failed to load or validate
+    Error,
 }
 
 #[derive(Component)]
 pub struct Goal;
 
+#[derive(Component)]
+pub struct Hazard;
+
+#[derive(Component)]
+pub struct Checkpoint;
+
 // To track despawn
 #[derive(Component, Default)]
 pub struct Despawnable {
@@ -33,12 +43,58 @@ impl Despawnable {
 pub struct LevelIndex(pub u8, pub bool);
 
 impl LevelIndex {
-    pub fn to_next_level(&mut self) {
-        self.0 = (self.0 + 1) % LEVEL_COUNT;
+    pub fn advance(&mut self, level_count: u8) {
+        // Validation refuses to play without levels, so this is only a guard
+        self.0 = (self.0 + 1).checked_rem(level_count).unwrap_or_default();
         self.1 = true;
     }
 }
 
+// Set once all level files are loaded
+#[derive(Resource, Default)]
+pub struct LevelCount(pub u8);
+
+// Deaths on the current level, reset when moving on to the next
+#[derive(Resource, Default)]
+pub struct DeathCount(pub u32);
+
+// Which link ids levers and pressure plates have turned on, missing ones are off.
+// Cleared on restart along with the level
+#[derive(Resource, Default)]
+pub struct LinkStates(pub HashMap<u32, bool>);
+
+impl LinkStates {
+    pub fn is_on(&self, id: u32) -> bool {
+        self.0.get(&id).copied().unwrap_or_default()
+    }
+}
+
+// Pressure plates the player is on and their link ids, so plates sharing an id
+// stay on until the last is left. Cleared on restart along with the level
+#[derive(Resource, Default)]
+pub struct PressedPlates(pub HashMap<Entity, u32>);
+
+impl PressedPlates {
+    pub fn any_pressed(&self, id: u32) -> bool {
+        self.0.values().any(|&pressed_id| pressed_id == id)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CollectedKey {
+    // Atlas index of the key's tile, to draw it in the HUD
+    pub index: usize,
+    pub layers: CollisionLayers,
+}
+
+// Keys picked up in the current level, in order. Cleared on restart
+#[derive(Resource, Default)]
+pub struct CollectedKeys(pub Vec<CollectedKey>);
+
+// Shown on the error screen in LevelState::Error
+#[derive(Resource, Default)]
+pub struct LevelErrors(pub Vec<String>);
+
 #[derive(Resource)]
 struct LevelTransitionTimer(Timer);
 
@@ -51,17 +107,20 @@ impl Plugin for LevelsPlugin {
             TimerMode::Repeating,
         )))
         .insert_resource(LevelIndex::default())
+        .insert_resource(LevelCount::default())
+        .insert_resource(LevelErrors::default())
+        .insert_resource(DeathCount::default())
+        .insert_resource(LinkStates::default())
+        .insert_resource(PressedPlates::default())
+        .insert_resource(CollectedKeys::default())
         .add_systems(Update, level_transition.run_if(in_transition_state))
-        .add_systems(OnExit(LevelState::End), (cleanup_entities, reset_camera));
+        .add_systems(OnExit(LevelState::End), cleanup_entities)
+        .add_systems(OnEnter(LevelState::Error), cleanup_entities);
     }
 }
 
 fn in_transition_state(state: Res<State<LevelState>>) -> bool {
-    match state.get() {
-        LevelState::End => true,
-        LevelState::Init => true,
-        _ => false,
-    }
+    matches!(state.get(), LevelState::End | LevelState::Init)
 }
 
 fn level_transition(
@@ -95,7 +154,17 @@ fn level_transition(
     }
 }
 
-fn cleanup_entities(mut commands: Commands, entities: Query<(Entity, &Despawnable)>) {
+fn cleanup_entities(
+    mut commands: Commands,
+    entities: Query<(Entity, &Despawnable)>,
+    mut link_states: ResMut<LinkStates>,
+    mut pressed_plates: ResMut<PressedPlates>,
+    mut collected_keys: ResMut<CollectedKeys>,
+) {
+    link_states.0.clear();
+    pressed_plates.0.clear();
+    collected_keys.0.clear();
+
     for (entity, despawnable) in &entities {
         match despawnable.has_children {
             true => commands.entity(entity).despawn_recursive(),
@@ -103,7 +172,3 @@ fn cleanup_entities(mut commands: Commands, entities: Query<(Entity, &Despawnabl
         }
     }
 }
-
-fn reset_camera(mut camera_query: Query<&mut Transform, With<Camera2d>>) {
-    *(camera_query.single_mut()) = Transform::default();
-}
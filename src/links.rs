@@ -0,0 +1,310 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{Collider, CollisionLayers, TriggerEntered, TriggerExited},
+    level::{CollectedKey, CollectedKeys, LevelState, LinkStates, PressedPlates},
+    player::Player,
+};
+
+// How much of a linked block shows while it is open
+const OPEN_BLOCK_ALPHA: f32 = 0.25;
+const LOCKED_GOAL_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwitchKind {
+    // On while something stands on it
+    Plate,
+    // Flips each time it is touched
+    Lever,
+}
+
+#[derive(Component, Debug)]
+pub struct LinkSwitch {
+    pub id: u32,
+    pub kind: SwitchKind,
+}
+
+// Solid with `layers` while closed, passable and faded while open
+#[derive(Component, Debug)]
+pub struct LinkedBlock {
+    pub id: u32,
+    pub inverted: bool,
+    pub layers: CollisionLayers,
+}
+
+// Turns its link id on for good when picked up
+#[derive(Component, Debug)]
+pub struct Key {
+    pub id: u32,
+}
+
+// A goal that opens once its link id is on
+#[derive(Component, Debug)]
+pub struct Lock {
+    pub id: u32,
+}
+
+// On locks whose id is off, check_goal_reached ignores goals that have it
+#[derive(Component, Debug)]
+pub struct Locked;
+
+#[derive(Event, Default)]
+pub struct KeyCollectedEvent;
+
+// Keeps lock sprites apart from the other linked sprites
+type LockOnly = (Without<LinkedBlock>, Without<LinkSwitch>);
+
+// Tint for keys only blue can pick up, the key tile is already yellow
+pub fn key_color(layers: CollisionLayers) -> Color {
+    if layers.contains(CollisionLayers::BLUE) {
+        Color::rgb(0.5, 0.7, 1.0)
+    } else {
+        Color::WHITE
+    }
+}
+
+impl LinkedBlock {
+    fn is_solid(&self, link_states: &LinkStates) -> bool {
+        link_states.is_on(self.id) == self.inverted
+    }
+}
+
+pub struct LinksPlugin;
+
+impl Plugin for LinksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<KeyCollectedEvent>().add_systems(
+            Update,
+            (collect_keys, press_switches, apply_link_states)
+                .chain()
+                .run_if(in_state(LevelState::Play)),
+        );
+    }
+}
+
+fn collect_keys(
+    mut commands: Commands,
+    mut trigger_event_reader: EventReader<TriggerEntered>,
+    player_query: Query<(), With<Player>>,
+    key_query: Query<(&Key, &TextureAtlas, &Collider)>,
+    mut link_states: ResMut<LinkStates>,
+    mut collected_keys: ResMut<CollectedKeys>,
+    mut key_event_writer: EventWriter<KeyCollectedEvent>,
+) {
+    for event in trigger_event_reader.read() {
+        if !player_query.contains(event.entity) {
+            continue;
+        }
+        let Ok((key, atlas, collider)) = key_query.get(event.other) else {
+            continue;
+        };
+
+        link_states.0.insert(key.id, true);
+        collected_keys.0.push(CollectedKey {
+            index: atlas.index,
+            layers: collider.membership,
+        });
+        key_event_writer.send_default();
+        commands.entity(event.other).despawn_recursive();
+    }
+}
+
+fn press_switches(
+    mut entered_event_reader: EventReader<TriggerEntered>,
+    mut exited_event_reader: EventReader<TriggerExited>,
+    player_query: Query<(), With<Player>>,
+    switch_query: Query<&LinkSwitch>,
+    mut link_states: ResMut<LinkStates>,
+    mut pressed_plates: ResMut<PressedPlates>,
+) {
+    for event in entered_event_reader.read() {
+        if !player_query.contains(event.entity) {
+            continue;
+        }
+        let Ok(switch) = switch_query.get(event.other) else {
+            continue;
+        };
+
+        let on = match switch.kind {
+            SwitchKind::Plate => {
+                pressed_plates.0.insert(event.other, switch.id);
+                true
+            }
+            SwitchKind::Lever => !link_states.is_on(switch.id),
+        };
+        link_states.0.insert(switch.id, on);
+    }
+
+    // By entity, as plates despawned by a reload can't be looked up anymore
+    for event in exited_event_reader.read() {
+        if !player_query.contains(event.entity) {
+            continue;
+        }
+        let Some(id) = pressed_plates.0.remove(&event.other) else {
+            continue;
+        };
+
+        if !pressed_plates.any_pressed(id) {
+            link_states.0.insert(id, false);
+        }
+    }
+}
+
+// Also catches blocks spawned after the state changed, like on hot reload
+fn apply_link_states(
+    mut commands: Commands,
+    link_states: Res<LinkStates>,
+    mut block_query: Query<(Ref<LinkedBlock>, &mut Collider, Option<&mut Sprite>)>,
+    mut switch_query: Query<(&LinkSwitch, &mut Sprite), Without<LinkedBlock>>,
+    mut lock_query: Query<(Entity, Ref<Lock>, Option<&mut Sprite>), LockOnly>,
+) {
+    for (entity, lock, sprite) in &mut lock_query {
+        if !link_states.is_changed() && !lock.is_added() {
+            continue;
+        }
+
+        let locked = !link_states.is_on(lock.id);
+        match locked {
+            true => commands.entity(entity).insert(Locked),
+            false => commands.entity(entity).remove::<Locked>(),
+        };
+
+        // Goals placed as objects have no sprite
+        if let Some(mut sprite) = sprite {
+            sprite.color = if locked {
+                LOCKED_GOAL_COLOR
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+
+    for (block, mut collider, sprite) in &mut block_query {
+        if !link_states.is_changed() && !block.is_added() {
+            continue;
+        }
+
+        let solid = block.is_solid(&link_states);
+        collider.membership = if solid {
+            block.layers
+        } else {
+            CollisionLayers::empty()
+        };
+
+        // Locked goals are blocked off without a sprite, the door shows the lock
+        if let Some(mut sprite) = sprite {
+            sprite
+                .color
+                .set_a(if solid { 1.0 } else { OPEN_BLOCK_ALPHA });
+        }
+    }
+
+    if !link_states.is_changed() {
+        return;
+    }
+
+    // Levers point the other way while on
+    for (switch, mut sprite) in &mut switch_query {
+        if switch.kind == SwitchKind::Lever {
+            sprite.flip_x = link_states.is_on(switch.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::player::{test_world::*, PlayerType};
+
+    fn spawn_plate(world: &mut World) -> Entity {
+        world
+            .spawn(LinkSwitch {
+                id: 1,
+                kind: SwitchKind::Plate,
+            })
+            .id()
+    }
+
+    // Fresh systems read every buffered event, so each run starts from a clean buffer
+    fn press(world: &mut World, entered: &[(Entity, Entity)], exited: &[(Entity, Entity)]) {
+        world.init_resource::<Events<TriggerEntered>>();
+        world.init_resource::<Events<TriggerExited>>();
+        world.init_resource::<LinkStates>();
+        world.init_resource::<PressedPlates>();
+        world.resource_mut::<Events<TriggerEntered>>().clear();
+        world.resource_mut::<Events<TriggerExited>>().clear();
+
+        for &(entity, other) in entered {
+            world.send_event(TriggerEntered {
+                entity,
+                other,
+                normal: Vec2::Y,
+            });
+        }
+        for &(entity, other) in exited {
+            world.send_event(TriggerExited {
+                entity,
+                other,
+                normal: Vec2::Y,
+            });
+        }
+
+        world.run_system_once(press_switches);
+    }
+
+    fn is_on(world: &World) -> bool {
+        world.resource::<LinkStates>().is_on(1)
+    }
+
+    #[test]
+    fn shared_plates_stay_on_until_all_are_left() {
+        let (mut world, player) = test_world(PlayerType::Blue);
+        let [first, second] = [(); 2].map(|_| spawn_plate(&mut world));
+
+        press(
+            &mut world,
+            &[(player, first), (player, second)],
+            &[(player, first)],
+        );
+        assert!(is_on(&world));
+
+        press(&mut world, &[], &[(player, second)]);
+        assert!(!is_on(&world));
+    }
+
+    #[test]
+    fn only_the_player_presses_plates() {
+        let (mut world, _) = test_world(PlayerType::Blue);
+        let plate = spawn_plate(&mut world);
+        let body = world.spawn_empty().id();
+
+        press(&mut world, &[(body, plate)], &[]);
+        assert!(!is_on(&world));
+    }
+
+    #[test]
+    fn locked_goal_blocks_until_its_key_is_collected() {
+        let (mut world, _) = test_world(PlayerType::Blue);
+        world.init_resource::<LinkStates>();
+        let block = world
+            .spawn((
+                Collider::default(),
+                LinkedBlock {
+                    id: 1,
+                    inverted: false,
+                    layers: CollisionLayers::NEUTRAL,
+                },
+            ))
+            .id();
+
+        world.run_system_once(apply_link_states);
+        let membership = |world: &World| world.get::<Collider>(block).unwrap().membership;
+        assert_eq!(membership(&world), CollisionLayers::NEUTRAL);
+
+        world.resource_mut::<LinkStates>().0.insert(1, true);
+        world.run_system_once(apply_link_states);
+        assert_eq!(membership(&world), CollisionLayers::empty());
+    }
+}
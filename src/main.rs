@@ -3,13 +3,20 @@ use bevy::{asset::AssetMetaCheck, prelude::*};
 mod audio;
 mod collision;
 mod level;
+mod links;
+mod movement;
+mod platform;
 mod player;
 mod tilemap;
 mod ui;
 
 use audio::AudioPlugin;
+use collision::CollisionPlugin;
 use level::{Fader, LevelState, LevelsPlugin};
-use player::PlayerPlugin;
+use links::LinksPlugin;
+use movement::MovementPlugin;
+use platform::PlatformPlugin;
+use player::{PlayerPlugin, SwitchRule};
 use tilemap::TilemapPlugin;
 use ui::UiPlugin;
 
@@ -17,17 +24,22 @@ fn main() {
     let mut app = App::new();
     app.init_state::<LevelState>()
         .insert_resource(AssetMetaCheck::Never)
+        .insert_resource(SwitchRule::PushOut)
         .add_plugins((
             DefaultPlugins.set(ImagePlugin::default_nearest()),
             AudioPlugin,
+            CollisionPlugin,
+            MovementPlugin,
+            PlatformPlugin,
             PlayerPlugin,
             TilemapPlugin,
             LevelsPlugin,
+            LinksPlugin,
             UiPlugin,
             #[cfg(debug_assertions)]
             {
                 (
-                    bevy::diagnostic::FrameTimeDiagnosticsPlugin::default(),
+                    bevy::diagnostic::FrameTimeDiagnosticsPlugin,
                     bevy::diagnostic::LogDiagnosticsPlugin::default(),
                 )
             },
@@ -0,0 +1,123 @@
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+const MOVEMENT_CONFIG_PATH: &str = "player.movement.ron";
+
+// Movement tuning, in pixels and seconds so it holds at any fixed timestep.
+// LevelState::Loading waits for the file, so the resource exists before Play
+#[derive(Asset, Resource, TypePath, Clone, Debug, Deserialize)]
+pub struct MovementConfig {
+    // Speeding up towards max_speed while a direction is held
+    pub run_acceleration: f32,
+    // Slowing down when no direction, or the opposite one, is held
+    pub deceleration: f32,
+    pub max_speed: f32,
+    // Fraction of acceleration and deceleration available in the air
+    pub air_control: f32,
+    pub gravity: f32,
+    // Fastest fall speed
+    pub terminal_velocity: f32,
+    // Peak height of a jump from the ground
+    pub jump_height: f32,
+    // How long after walking off a ledge a jump still works
+    pub coyote_time: f32,
+    // How long before landing a jump press is remembered
+    pub jump_buffer: f32,
+    // Multiplier on upward speed when jump is released early
+    pub jump_cut: f32,
+    // Fastest fall speed while pressed against a wall
+    pub wall_slide_speed: f32,
+    // Sideways speed away from the wall when jumping off it
+    pub wall_jump_speed: f32,
+}
+
+impl MovementConfig {
+    // Launch speed that peaks at jump_height under gravity
+    pub fn jump_velocity(&self) -> f32 {
+        (2.0 * self.gravity * self.jump_height).sqrt()
+    }
+}
+
+#[derive(Resource)]
+pub struct MovementConfigHandle(pub Handle<MovementConfig>);
+
+#[derive(Debug, Error)]
+pub enum MovementConfigLoaderError {
+    #[error("Could not read movement config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse movement config RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct MovementConfigLoader;
+
+impl AssetLoader for MovementConfigLoader {
+    type Asset = MovementConfig;
+    type Settings = ();
+    type Error = MovementConfigLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<MovementConfig>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["movement.ron"]
+    }
+}
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MovementConfig>()
+            .init_asset_loader::<MovementConfigLoader>()
+            .add_systems(Startup, load_movement_config)
+            .add_systems(Update, apply_movement_config);
+    }
+}
+
+fn load_movement_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(MovementConfigHandle(
+        asset_server.load(MOVEMENT_CONFIG_PATH),
+    ));
+}
+
+// Also picks up edits when hot reloading
+fn apply_movement_config(
+    mut config_events: EventReader<AssetEvent<MovementConfig>>,
+    config_handle: Res<MovementConfigHandle>,
+    configs: Res<Assets<MovementConfig>>,
+    mut commands: Commands,
+) {
+    let changed = config_events
+        .read()
+        .filter(|event| {
+            event.is_loaded_with_dependencies(&config_handle.0)
+                || event.is_modified(&config_handle.0)
+        })
+        .count()
+        > 0;
+
+    if !changed {
+        return;
+    }
+
+    if let Some(config) = configs.get(&config_handle.0) {
+        commands.insert_resource(config.clone());
+    }
+}
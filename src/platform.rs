@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{MovingCollider, PhysicsSet},
+    level::LevelState,
+};
+
+// Travels through world space waypoints in order, looping back to the first.
+// Speed is in pixels per second
+#[derive(Component, Debug)]
+pub struct MovingPlatform {
+    pub waypoints: Vec<Vec2>,
+    pub speed: f32,
+    // Index of the waypoint being headed for
+    pub next: usize,
+}
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            follow_waypoints
+                .before(PhysicsSet)
+                .run_if(in_state(LevelState::Play)),
+        );
+    }
+}
+
+// Sets the velocity PhysicsSet moves by, landing exactly on each waypoint
+fn follow_waypoints(
+    time: Res<Time>,
+    mut platform_query: Query<(&mut MovingPlatform, &mut MovingCollider, &Transform)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (mut platform, mut moving, transform) in &mut platform_query {
+        let Some(&target) = platform.waypoints.get(platform.next) else {
+            continue;
+        };
+
+        let to_target = target - transform.translation.truncate();
+        if to_target.length() <= platform.speed * delta {
+            moving.velocity = to_target / delta;
+            platform.next = (platform.next + 1) % platform.waypoints.len();
+        } else {
+            moving.velocity = to_target.normalize() * platform.speed;
+        }
+    }
+}
@@ -2,16 +2,22 @@ use std::time::Duration;
 
 use crate::{
     collision::*,
-    level::{Despawnable, Goal, LevelIndex, LevelState},
+    level::{
+        Checkpoint, DeathCount, Despawnable, Goal, Hazard, LevelCount, LevelIndex, LevelState,
+    },
+    links::Locked,
+    movement::MovementConfig,
+    tilemap::{LevelBounds, LevelReloadedEvent, LoadLevelSet},
 };
-use bevy::{math::bounding::IntersectsVolume, prelude::*};
+use bevy::{input::InputSystem, prelude::*};
 
 const PLAYER_ANIMATION_SPEED: f32 = 0.2;
 const PLAYER_COLLIDER_SIZE: f32 = 14.0;
 const PLAYER_SCALE: f32 = 2.0 / 3.0;
 
-const GRAVITY: f32 = 0.1;
-const JUMP_VELOCITY: f32 = 2.5;
+const DEATH_DURATION: f32 = 0.6;
+// How far below the level the player can fall before dying
+const KILL_PLANE_DEPTH: f32 = 64.0;
 
 // Not a Bevy state, should pertain only to Player
 #[derive(Default)]
@@ -20,52 +26,162 @@ enum PlayerAnimationState {
     Idle,
     Air,
     Walk,
+    WallSlide,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-enum PlayerType {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayerType {
     #[default]
-    Blue = 0,
-    Yellow = 2,
+    Blue,
+    Yellow,
 }
 
+impl PlayerType {
+    // First frame of this type's row in characters.png
+    fn atlas_index(self) -> usize {
+        match self {
+            PlayerType::Blue => 0,
+            PlayerType::Yellow => 2,
+        }
+    }
+
+    // The colored blocks this type can stand on
+    fn collision_layer(self) -> CollisionLayers {
+        match self {
+            PlayerType::Blue => CollisionLayers::BLUE,
+            PlayerType::Yellow => CollisionLayers::YELLOW,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "blue" => Some(PlayerType::Blue),
+            "yellow" => Some(PlayerType::Yellow),
+            _ => None,
+        }
+    }
+}
+
+// Solid against neutral blocks and blocks of the player's own color
+pub fn player_collider(player_type: PlayerType) -> Collider {
+    Collider {
+        width: PLAYER_COLLIDER_SIZE,
+        height: PLAYER_COLLIDER_SIZE,
+        membership: player_type.collision_layer(),
+        filter: CollisionLayers::NEUTRAL | player_type.collision_layer(),
+        ..default()
+    }
+}
+
+// Where the player starts in the current level, set when the level loads
+#[derive(Resource, Clone, Default)]
+pub struct PlayerSpawn {
+    pub position: Vec2,
+    pub player_type: PlayerType,
+}
+
+// Where deaths and going back to the checkpoint put the player,
+// the level start until a checkpoint is touched
+#[derive(Resource, Default)]
+struct RespawnPoint(PlayerSpawn);
+
 #[derive(Component, Default)]
-struct Player {
-    velocity: (f32, f32),
-    can_jump: bool,
+pub struct Player {
     animation_state: PlayerAnimationState,
     player_type: PlayerType,
+    // Seconds left to jump after leaving the ground
+    coyote_timer: f32,
+    // Seconds left for a jump press to take effect on landing
+    jump_buffer_timer: f32,
+    // Rising from a jump, so releasing the button cuts it short
+    jumping: bool,
 }
 
 #[derive(Component, Deref, DerefMut)]
 struct AnimationTimer(Timer);
 
+// Playing the death animation, respawns once the timer finishes
+#[derive(Component, Deref, DerefMut)]
+struct Dying(Timer);
+
+type AlivePlayer = (With<Player>, Without<Dying>);
+
 #[derive(Event, Default)]
 pub struct JumpEvent;
 
 #[derive(Event, Default)]
 pub struct SwitchEvent;
 
+// A switch that was refused because the player is inside the other color's blocks
+#[derive(Event, Default)]
+pub struct SwitchBlockedEvent;
+
+// What happens when switching would put the player inside the other color's blocks
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwitchRule {
+    // Stay as we are and send a SwitchBlockedEvent
+    #[default]
+    Refuse,
+    // Move to the nearest clear spot, refusing only if there isn't one next to the blocks
+    PushOut,
+}
+
 #[derive(Event, Default)]
 pub struct GoalEvent;
 
+#[derive(Event, Default)]
+pub struct DeathEvent;
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<JumpEvent>()
             .add_event::<SwitchEvent>()
+            .add_event::<SwitchBlockedEvent>()
             .add_event::<GoalEvent>()
-            .add_systems(OnEnter(LevelState::Init), player_init)
+            .add_event::<DeathEvent>()
+            .insert_resource(PlayerSpawn::default())
+            .init_resource::<RespawnPoint>()
+            .init_resource::<SwitchRule>()
+            .add_systems(OnEnter(LevelState::Init), player_init.after(LoadLevelSet))
+            // Presses are read once per frame, as a frame can run the fixed update
+            // twice or not at all
+            .add_systems(
+                PreUpdate,
+                (buffer_jump_input, switch_player_type, restart_level)
+                    .after(InputSystem)
+                    .run_if(in_state(LevelState::Play)),
+            )
             .add_systems(
                 FixedUpdate,
-                (handle_player_input, move_player, camera_follow)
-                    .chain()
+                (
+                    handle_player_input.before(PhysicsSet),
+                    (update_player_state, camera_follow)
+                        .chain()
+                        .after(PhysicsSet),
+                )
                     .run_if(in_state(LevelState::Play)),
             )
             .add_systems(
                 Update,
-                (animate_player, check_goal_reached).run_if(in_state(LevelState::Play)),
+                (
+                    animate_player,
+                    check_goal_reached,
+                    check_checkpoint_reached,
+                    return_to_checkpoint,
+                    check_player_death,
+                    animate_death,
+                    // The goal ends Play, so this only gets the one frame to see it
+                    reset_death_count.after(check_goal_reached),
+                )
+                    .run_if(in_state(LevelState::Play)),
+            )
+            .add_systems(
+                PostUpdate,
+                keep_player_on_reload
+                    .after(CollisionWorldSet)
+                    .run_if(in_state(LevelState::Play)),
             );
     }
 }
@@ -74,28 +190,36 @@ fn player_init(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    spawn: Res<PlayerSpawn>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
 ) {
+    // A full restart forgets the checkpoint
+    commands.insert_resource(RespawnPoint(spawn.clone()));
+
+    // Start the camera on the player, camera_follow takes over in Play
+    *(camera_query.single_mut()) = Transform::from_translation(spawn.position.extend(0.0));
+
     let texture = asset_server.load("characters.png");
-    let layout = TextureAtlasLayout::from_grid(Vec2::splat(24.), 4, 1, None, None);
+    let layout = TextureAtlasLayout::from_grid(Vec2::splat(24.), 4, 2, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 
     commands.spawn((
         SpriteBundle {
             texture,
-            transform: Transform::from_scale(Vec3::splat(PLAYER_SCALE)),
+            transform: Transform::from_translation(spawn.position.extend(0.0))
+                .with_scale(Vec3::splat(PLAYER_SCALE)),
             ..default()
         },
         TextureAtlas {
             layout: texture_atlas_layout,
-            index: 0,
+            index: spawn.player_type.atlas_index(),
         },
-        Player::default(),
-        Collider {
-            width: PLAYER_COLLIDER_SIZE,
-            height: PLAYER_COLLIDER_SIZE,
-            layer_mask: 0b11,
-            is_trigger: false,
+        Player {
+            player_type: spawn.player_type,
+            ..default()
         },
+        KinematicBody::default(),
+        player_collider(spawn.player_type),
         AnimationTimer(Timer::from_seconds(
             PLAYER_ANIMATION_SPEED,
             TimerMode::Repeating,
@@ -104,14 +228,37 @@ fn player_init(
     ));
 }
 
-fn handle_player_input(
+// Held until the next fixed update that can jump
+fn buffer_jump_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    movement_config: Res<MovementConfig>,
+    mut query: Query<&mut Player>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        query.single_mut().jump_buffer_timer = movement_config.jump_buffer;
+    }
+}
+
+fn restart_level(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Player, &mut AnimationTimer, &mut Collider)>,
     mut next_state: ResMut<NextState<LevelState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        next_state.set(LevelState::End);
+    }
+}
+
+fn handle_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    movement_config: Res<MovementConfig>,
+    mut query: Query<(&mut Player, &mut KinematicBody), Without<Dying>>,
     mut jump_event_writer: EventWriter<JumpEvent>,
-    mut switch_event_writer: EventWriter<SwitchEvent>,
 ) {
-    let (mut player, mut timer, mut collider) = query.single_mut();
+    let Ok((mut player, mut body)) = query.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_seconds();
     let mut direction = 0.0;
 
     if keyboard_input.pressed(KeyCode::ArrowLeft) {
@@ -122,126 +269,177 @@ fn handle_player_input(
         direction += 1.0;
     }
 
-    player.velocity.0 = direction;
-    player.velocity.1 -= GRAVITY;
+    // Holding down drops through one-way platforms
+    body.drop_through = keyboard_input.pressed(KeyCode::ArrowDown);
+
+    // Accelerate towards the held direction, decelerate when letting go or turning
+    let target_speed = direction * movement_config.max_speed;
+    let speeding_up = direction != 0.0 && body.velocity.x * direction >= 0.0;
+    let mut rate = if speeding_up {
+        movement_config.run_acceleration
+    } else {
+        movement_config.deceleration
+    };
+    if !body.contacts.grounded {
+        rate *= movement_config.air_control;
+    }
+
+    let max_change = rate * delta;
+    body.velocity.x += (target_speed - body.velocity.x).clamp(-max_change, max_change);
+
+    // Only standing on something counts, not bumping a ceiling or wall
+    if body.contacts.grounded {
+        player.coyote_timer = movement_config.coyote_time;
+    } else {
+        player.coyote_timer = (player.coyote_timer - delta).max(0.0);
+    }
+
+    // Walls of the other color are passed through, so clinging follows the current color.
+    // Only once falling, so chaining wall jumps can't climb a single wall
+    let wall_sliding = !body.contacts.grounded && body.contacts.on_wall() && body.velocity.y <= 0.0;
+
+    // Jumping off a wall also kicks away from it
+    let wall_kick = if body.contacts.wall_left { 1.0 } else { -1.0 };
+    let ground_jump = player.coyote_timer > 0.0;
 
-    if player.can_jump && keyboard_input.just_pressed(KeyCode::Space) {
-        player.velocity.1 = JUMP_VELOCITY;
-        player.can_jump = false;
+    if player.jump_buffer_timer > 0.0 && (ground_jump || wall_sliding) {
+        body.velocity.y = movement_config.jump_velocity();
+        if !ground_jump {
+            body.velocity.x = wall_kick * movement_config.wall_jump_speed;
+        }
+        body.contacts.grounded = false;
+        player.coyote_timer = 0.0;
+        player.jump_buffer_timer = 0.0;
+        player.jumping = true;
         jump_event_writer.send_default();
     }
+    player.jump_buffer_timer = (player.jump_buffer_timer - delta).max(0.0);
 
-    // Restart
-    if keyboard_input.just_pressed(KeyCode::KeyR) {
-        next_state.set(LevelState::End);
+    if wall_sliding {
+        body.velocity.y = body.velocity.y.max(-movement_config.wall_slide_speed);
     }
 
-    // Switch types
-    if keyboard_input.just_pressed(KeyCode::ShiftLeft) {
-        player.player_type = match player.player_type {
-            PlayerType::Blue => PlayerType::Yellow,
-            PlayerType::Yellow => PlayerType::Blue,
-        };
-        collider.layer_mask = (player.player_type as u8) + 3;
-        timer.tick(Duration::from_secs_f32(PLAYER_ANIMATION_SPEED));
-        switch_event_writer.send_default();
+    // Letting go early makes for a shorter jump
+    if player.jumping && body.velocity.y > 0.0 && !keyboard_input.pressed(KeyCode::Space) {
+        body.velocity.y *= movement_config.jump_cut;
+        player.jumping = false;
+    }
+    if body.velocity.y <= 0.0 {
+        player.jumping = false;
     }
 }
 
-fn move_player(
-    mut player_query: Query<(&mut Player, &mut Transform, &mut Sprite, &Collider)>,
-    collider_query: Query<(&GlobalTransform, &Collider), Without<Player>>,
+fn switch_player_type(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    switch_rule: Res<SwitchRule>,
+    collision_world: Res<CollisionWorld>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Player,
+            &mut Transform,
+            &mut AnimationTimer,
+            &mut Collider,
+        ),
+        Without<Dying>,
+    >,
+    mut switch_event_writer: EventWriter<SwitchEvent>,
+    mut blocked_event_writer: EventWriter<SwitchBlockedEvent>,
 ) {
-    let (mut player, mut player_transform, mut player_sprite, player_collider) =
-        player_query.single_mut();
+    if !keyboard_input.just_pressed(KeyCode::ShiftLeft) {
+        return;
+    }
 
-    let next_player_pos = Vec2::new(
-        player_transform.translation.x + player.velocity.0,
-        player_transform.translation.y + player.velocity.1,
-    );
-    let next_player_bounding_box = player_collider.get_aabb2d(next_player_pos);
-
-    // Collision detection
-    let close_collider_transforms = collider_query
-        .iter()
-        .filter(|(transform, collider)| {
-            // Layer mask check
-            (player_collider.layer_mask & collider.layer_mask != 0)
-                && !collider.is_trigger
-                && next_player_bounding_box
-                    .intersects(&collider.get_aabb2d(transform.translation().truncate()))
-        })
-        .collect::<Vec<(&GlobalTransform, &Collider)>>();
+    let Ok((entity, mut player, mut transform, mut timer, mut collider)) = query.get_single_mut()
+    else {
+        return;
+    };
+    let player_type = match player.player_type {
+        PlayerType::Blue => PlayerType::Yellow,
+        PlayerType::Yellow => PlayerType::Blue,
+    };
+    let filter = CollisionLayers::NEUTRAL | player_type.collision_layer();
+    let bounding_box = collider.get_aabb2d(transform.translation.truncate());
+
+    if !collision_world
+        .overlapping(entity, bounding_box, filter)
+        .is_empty()
+    {
+        let push = match *switch_rule {
+            SwitchRule::Refuse => None,
+            SwitchRule::PushOut => collision_world.push_out(entity, bounding_box, filter),
+        };
 
-    // Technically means that bonking on ceiling allows you to jump
-    player.can_jump = check_player_collision(
-        &mut player,
-        &player_transform,
-        &player_collider,
-        &close_collider_transforms,
-    );
+        let Some(push) = push else {
+            blocked_event_writer.send_default();
+            return;
+        };
+        transform.translation += push.extend(0.0);
+    }
+
+    set_player_type(&mut player, &mut collider, player_type);
+    timer.tick(Duration::from_secs_f32(PLAYER_ANIMATION_SPEED));
+    switch_event_writer.send_default();
+}
+
+fn set_player_type(player: &mut Player, collider: &mut Collider, player_type: PlayerType) {
+    player.player_type = player_type;
+    *collider = player_collider(player_type);
+}
 
-    if player.velocity.0 > 0.0 {
+fn update_player_state(mut player_query: Query<(&mut Player, &KinematicBody, &mut Sprite)>) {
+    let (mut player, body, mut player_sprite) = player_query.single_mut();
+
+    if body.velocity.x > 0.0 {
         player_sprite.flip_x = true;
-    } else if player.velocity.0 < 0.0 {
+    } else if body.velocity.x < 0.0 {
         player_sprite.flip_x = false;
     }
 
-    // If a y collision occured, we are in Air state
+    // If we are falling against a wall, we are in WallSlide state
+    // If we are otherwise off the ground, we are in Air state
     // If we have velocity, we are in Walk state
     // Otherwise we are in Idle state
-    player.animation_state = if !player.can_jump {
-        PlayerAnimationState::Air
-    } else if player.velocity.0.abs() > 0.0 {
+    player.animation_state = if !body.contacts.grounded {
+        if body.contacts.on_wall() && body.velocity.y < 0.0 {
+            PlayerAnimationState::WallSlide
+        } else {
+            PlayerAnimationState::Air
+        }
+    } else if body.velocity.x.abs() > 0.0 {
         PlayerAnimationState::Walk
     } else {
         PlayerAnimationState::Idle
     };
-
-    player_transform.translation.x += player.velocity.0;
-    player_transform.translation.y += player.velocity.1;
 }
 
-fn check_player_collision(
-    player: &mut Player,
-    player_transform: &Transform,
-    player_collider: &Collider,
-    close_collider_transforms: &[(&GlobalTransform, &Collider)],
-) -> bool {
-    let mut y_collision = false;
-
-    let next_player_pos_y = Vec2::new(
-        player_transform.translation.x,
-        player_transform.translation.y + player.velocity.1,
-    );
-
-    for &(transform, collider) in close_collider_transforms {
-        let next_player_y_collider = player_collider.get_aabb2d(next_player_pos_y);
-        let collider = collider.get_aabb2d(transform.translation().truncate());
+// Runs once the rebuilt tilemap is in the CollisionWorld
+fn keep_player_on_reload(
+    mut reload_event_reader: EventReader<LevelReloadedEvent>,
+    spawn: Res<PlayerSpawn>,
+    mut respawn_point: ResMut<RespawnPoint>,
+    mut player_query: Query<(Entity, &mut Transform, &Collider), With<Player>>,
+    collision_world: Res<CollisionWorld>,
+) {
+    let Some(reload_event) = reload_event_reader.read().last() else {
+        return;
+    };
 
-        if next_player_y_collider.intersects(&collider) {
-            player.velocity.1 = 0.0;
-            y_collision = true;
-            break;
-        }
-    }
+    // The checkpoint may have moved or gone with the edit
+    respawn_point.0 = spawn.clone();
 
-    let next_player_pos_x = Vec2::new(
-        player_transform.translation.x + player.velocity.0,
-        player_transform.translation.y,
-    );
+    let (player_entity, mut player_transform, player_collider) = player_query.single_mut();
+    let player_pos = player_transform.translation.truncate();
+    let player_bounding_box = player_collider.get_aabb2d(player_pos);
 
-    for &(transform, collider) in close_collider_transforms {
-        let next_player_x_collider = player_collider.get_aabb2d(next_player_pos_x);
-        let collider = collider.get_aabb2d(transform.translation().truncate());
+    let blocked = !collision_world
+        .overlapping(player_entity, player_bounding_box, player_collider.filter)
+        .is_empty();
 
-        if next_player_x_collider.intersects(&collider) {
-            player.velocity.0 = 0.0;
-            break;
-        }
+    // Otherwise stay put so the edit can be tried from where we are
+    if blocked || !reload_event.bounds.contains(player_pos) {
+        player_transform.translation = spawn.position.extend(player_transform.translation.z);
     }
-
-    y_collision
 }
 
 fn camera_follow(
@@ -257,26 +455,194 @@ fn camera_follow(
     camera_transform.translation.y = player_transform.translation.y;
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_goal_reached(
-    player_query: Query<(&Transform, &Collider), With<Player>>,
-    goal_query: Query<(&GlobalTransform, &Collider), With<Goal>>,
+    mut trigger_event_reader: EventReader<TriggerEntered>,
+    mut unlocked_goals: RemovedComponents<Locked>,
+    collision_world: Res<CollisionWorld>,
+    player_query: Query<Entity, AlivePlayer>,
+    goal_query: Query<(), (With<Goal>, Without<Locked>)>,
     mut next_state: ResMut<NextState<LevelState>>,
     mut level_index: ResMut<LevelIndex>,
+    level_count: Res<LevelCount>,
     mut goal_event_writer: EventWriter<GoalEvent>,
 ) {
-    let (player_transform, player_collider) = player_query.single();
-    let player_bounding_box = player_collider.get_aabb2d(player_transform.translation.truncate());
+    // Goals are on the neutral layer, so either player type reaches them
+    let goal_entered = trigger_event_reader
+        .read()
+        .filter(|event| player_query.contains(event.entity) && goal_query.contains(event.other))
+        .count()
+        > 0;
+
+    // Entering a locked goal used up its TriggerEntered, so look again once it unlocks
+    let goal_unlocked_around_player = unlocked_goals
+        .read()
+        .filter(|&goal| {
+            goal_query.contains(goal)
+                && player_query
+                    .iter()
+                    .any(|player| collision_world.touching(player, goal))
+        })
+        .count()
+        > 0;
 
-    for (goal_transform, goal_collider) in &goal_query {
-        let goal_bounding_box = goal_collider.get_aabb2d(goal_transform.translation().truncate());
+    if goal_entered || goal_unlocked_around_player {
+        // We win
+        level_index.advance(level_count.0);
+        goal_event_writer.send_default();
+        next_state.set(LevelState::End);
+    }
+}
 
-        if player_bounding_box.intersects(&goal_bounding_box) {
-            // We win
-            level_index.to_next_level();
-            goal_event_writer.send_default();
-            next_state.set(LevelState::End);
-        }
+fn check_checkpoint_reached(
+    mut trigger_event_reader: EventReader<TriggerEntered>,
+    player_query: Query<&Player, AlivePlayer>,
+    checkpoint_query: Query<&GlobalTransform, With<Checkpoint>>,
+    mut respawn_point: ResMut<RespawnPoint>,
+) {
+    for event in trigger_event_reader.read() {
+        let (Ok(player), Ok(checkpoint_transform)) = (
+            player_query.get(event.entity),
+            checkpoint_query.get(event.other),
+        ) else {
+            continue;
+        };
+
+        respawn_point.0 = PlayerSpawn {
+            position: checkpoint_transform.translation().truncate(),
+            player_type: player.player_type,
+        };
+    }
+}
+
+// Back to the last checkpoint without rebuilding the level
+fn return_to_checkpoint(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    respawn_point: Res<RespawnPoint>,
+    mut player_query: Query<
+        (
+            &mut Player,
+            &mut Transform,
+            &mut KinematicBody,
+            &mut Collider,
+        ),
+        Without<Dying>,
+    >,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+
+    let Ok((mut player, mut transform, mut body, mut collider)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    respawn(
+        &respawn_point.0,
+        &mut player,
+        &mut transform,
+        &mut body,
+        &mut collider,
+    );
+}
+
+fn respawn(
+    spawn: &PlayerSpawn,
+    player: &mut Player,
+    transform: &mut Transform,
+    body: &mut KinematicBody,
+    collider: &mut Collider,
+) {
+    transform.translation = spawn.position.extend(transform.translation.z);
+    transform.rotation = Quat::IDENTITY;
+    transform.scale = Vec3::splat(PLAYER_SCALE);
+    *body = KinematicBody::default();
+    set_player_type(player, collider, spawn.player_type);
+}
+
+fn check_player_death(
+    mut commands: Commands,
+    mut trigger_event_reader: EventReader<TriggerEntered>,
+    mut player_query: Query<(Entity, &Transform, &mut KinematicBody), AlivePlayer>,
+    hazard_query: Query<(), With<Hazard>>,
+    level_bounds: Res<LevelBounds>,
+    mut death_count: ResMut<DeathCount>,
+    mut death_event_writer: EventWriter<DeathEvent>,
+) {
+    let hazards_touched = trigger_event_reader
+        .read()
+        .filter(|event| hazard_query.contains(event.other))
+        .map(|event| event.entity)
+        .collect::<Vec<Entity>>();
+
+    let Ok((entity, transform, mut body)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let fell = transform.translation.y < level_bounds.0.min.y - KILL_PLANE_DEPTH;
+    if !fell && !hazards_touched.contains(&entity) {
+        return;
+    }
+
+    // Hold still for the animation
+    body.velocity = Vec2::ZERO;
+    body.gravity_scale = 0.0;
+
+    commands
+        .entity(entity)
+        .insert(Dying(Timer::from_seconds(DEATH_DURATION, TimerMode::Once)));
+    death_count.0 += 1;
+    death_event_writer.send_default();
+}
+
+// The counter is per level
+fn reset_death_count(
+    mut goal_event_reader: EventReader<GoalEvent>,
+    mut death_count: ResMut<DeathCount>,
+) {
+    if goal_event_reader.read().count() > 0 {
+        death_count.0 = 0;
+    }
+}
+
+// Spins and shrinks away, then puts the player back at the last checkpoint
+fn animate_death(
+    mut commands: Commands,
+    time: Res<Time>,
+    respawn_point: Res<RespawnPoint>,
+    mut player_query: Query<(
+        Entity,
+        &mut Player,
+        &mut Dying,
+        &mut Transform,
+        &mut KinematicBody,
+        &mut Collider,
+    )>,
+) {
+    let Ok((entity, mut player, mut dying, mut transform, mut body, mut collider)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    dying.tick(time.delta());
+    let progress = dying.fraction();
+    transform.rotation = Quat::from_rotation_z(progress * std::f32::consts::TAU);
+    transform.scale = Vec3::splat(PLAYER_SCALE * (1.0 - progress));
+
+    if !dying.finished() {
+        return;
     }
+
+    respawn(
+        &respawn_point.0,
+        &mut player,
+        &mut transform,
+        &mut body,
+        &mut collider,
+    );
+    commands.entity(entity).remove::<Dying>();
 }
 
 fn animate_player(
@@ -287,11 +653,235 @@ fn animate_player(
 
     timer.tick(time.delta());
     if timer.just_finished() {
-        atlas.index = (player.player_type as usize)
+        atlas.index = player.player_type.atlas_index()
             + match player.animation_state {
                 PlayerAnimationState::Idle => 0,
                 PlayerAnimationState::Air => 1,
                 PlayerAnimationState::Walk => (atlas.index % 2 + 1) % 2,
+                // Second row, below the Idle and Air frames
+                PlayerAnimationState::WallSlide => 4 + (atlas.index % 2 + 1) % 2,
+            };
+    }
+}
+
+// Shared setup for system tests
+#[cfg(test)]
+pub mod test_world {
+    use super::*;
+
+    // A player of `player_type` at the origin, already in the CollisionWorld
+    pub fn test_world(player_type: PlayerType) -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<CollisionWorld>();
+
+        let player = spawn_collider(
+            &mut world,
+            (
+                Player {
+                    player_type,
+                    ..default()
+                },
+                Transform::default(),
+                AnimationTimer(Timer::from_seconds(
+                    PLAYER_ANIMATION_SPEED,
+                    TimerMode::Repeating,
+                )),
+            ),
+            player_collider(player_type),
+            Vec2::ZERO,
+        );
+
+        (world, player)
+    }
+
+    // Spawns `bundle` with `collider`, and adds it to the CollisionWorld at `center`
+    pub fn spawn_collider(
+        world: &mut World,
+        bundle: impl Bundle,
+        collider: Collider,
+        center: Vec2,
+    ) -> Entity {
+        let entity = world.spawn_empty().id();
+        world.resource_mut::<CollisionWorld>().insert(
+            entity,
+            collider.get_aabb2d(center),
+            &collider,
+        );
+        world.entity_mut(entity).insert((bundle, collider));
+        entity
+    }
+
+    pub fn events<E: Event>(world: &World) -> usize {
+        world.resource::<Events<E>>().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::{test_world::*, *};
+
+    fn add_yellow_blocks(world: &mut World, blocks: &[Vec2]) {
+        for &center in blocks {
+            let collider = Collider {
+                width: 16.0,
+                height: 16.0,
+                membership: CollisionLayers::YELLOW,
+                ..default()
             };
+            spawn_collider(world, (), collider, center);
+        }
+    }
+
+    // Presses Left Shift and runs one switch under `switch_rule`
+    fn switch(world: &mut World, switch_rule: SwitchRule) {
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::ShiftLeft);
+        world.insert_resource(keyboard_input);
+        world.insert_resource(switch_rule);
+        world.init_resource::<Events<SwitchEvent>>();
+        world.init_resource::<Events<SwitchBlockedEvent>>();
+
+        world.run_system_once(switch_player_type);
+    }
+
+    fn player_type(world: &World, player: Entity) -> PlayerType {
+        world.get::<Player>(player).unwrap().player_type
+    }
+
+    fn position(world: &World, player: Entity) -> Vec2 {
+        world
+            .get::<Transform>(player)
+            .unwrap()
+            .translation
+            .truncate()
+    }
+
+    #[test]
+    fn switches_when_clear() {
+        let (mut world, player) = test_world(PlayerType::Blue);
+        add_yellow_blocks(&mut world, &[Vec2::new(32.0, 0.0)]);
+        switch(&mut world, SwitchRule::Refuse);
+
+        assert_eq!(player_type(&world, player), PlayerType::Yellow);
+        assert_eq!(position(&world, player), Vec2::ZERO);
+        assert_eq!(events::<SwitchEvent>(&world), 1);
+        assert_eq!(events::<SwitchBlockedEvent>(&world), 0);
+    }
+
+    #[test]
+    fn refuse_keeps_type_inside_block() {
+        let (mut world, player) = test_world(PlayerType::Blue);
+        add_yellow_blocks(&mut world, &[Vec2::new(4.0, 0.0)]);
+        switch(&mut world, SwitchRule::Refuse);
+
+        assert_eq!(player_type(&world, player), PlayerType::Blue);
+        assert_eq!(position(&world, player), Vec2::ZERO);
+        assert_eq!(events::<SwitchEvent>(&world), 0);
+        assert_eq!(events::<SwitchBlockedEvent>(&world), 1);
+    }
+
+    #[test]
+    fn push_out_takes_shortest_clear_push() {
+        // Player spans -7..7, the block 4 - 8..4 + 8, so leaving left is shortest
+        let (mut world, player) = test_world(PlayerType::Blue);
+        add_yellow_blocks(&mut world, &[Vec2::new(4.0, 0.0)]);
+        switch(&mut world, SwitchRule::PushOut);
+
+        assert_eq!(player_type(&world, player), PlayerType::Yellow);
+        assert_eq!(position(&world, player), Vec2::new(-11.0, 0.0));
+        assert_eq!(events::<SwitchEvent>(&world), 1);
+        assert_eq!(events::<SwitchBlockedEvent>(&world), 0);
+    }
+
+    #[test]
+    fn push_out_skips_pushes_into_other_blocks() {
+        // Left is shortest but blocked, so up is the next clear push
+        let (mut world, player) = test_world(PlayerType::Blue);
+        add_yellow_blocks(&mut world, &[Vec2::new(4.0, 0.0), Vec2::new(-20.0, 0.0)]);
+        switch(&mut world, SwitchRule::PushOut);
+
+        assert_eq!(player_type(&world, player), PlayerType::Yellow);
+        assert_eq!(position(&world, player), Vec2::new(0.0, 15.0));
+    }
+
+    #[test]
+    fn push_out_refuses_without_clear_push() {
+        // Every push out of the middle tile lands in one of its neighbours
+        let blocks = (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| Vec2::new(x as f32, y as f32) * 16.0))
+            .collect::<Vec<Vec2>>();
+        let (mut world, player) = test_world(PlayerType::Blue);
+        add_yellow_blocks(&mut world, &blocks);
+        switch(&mut world, SwitchRule::PushOut);
+
+        assert_eq!(player_type(&world, player), PlayerType::Blue);
+        assert_eq!(position(&world, player), Vec2::ZERO);
+        assert_eq!(events::<SwitchEvent>(&world), 0);
+        assert_eq!(events::<SwitchBlockedEvent>(&world), 1);
+    }
+
+    // Spawns a locked goal at `goal_position`, unlocks it and checks for the goal
+    fn unlock_goal(world: &mut World, goal_position: Vec2) {
+        world.init_resource::<NextState<LevelState>>();
+        world.init_resource::<LevelIndex>();
+        world.insert_resource(LevelCount(2));
+        world.init_resource::<Events<TriggerEntered>>();
+        world.init_resource::<Events<GoalEvent>>();
+
+        let collider = Collider {
+            width: 1.0,
+            height: 1.0,
+            membership: CollisionLayers::NEUTRAL,
+            is_trigger: true,
+            ..default()
+        };
+        let goal = spawn_collider(world, (Goal, Locked), collider, goal_position);
+        world.entity_mut(goal).remove::<Locked>();
+
+        world.run_system_once(check_goal_reached);
+    }
+
+    #[test]
+    fn reaches_goal_unlocked_around_player() {
+        let (mut world, _) = test_world(PlayerType::Blue);
+        unlock_goal(&mut world, Vec2::new(4.0, 0.0));
+
+        assert_eq!(events::<GoalEvent>(&world), 1);
+        assert_eq!(world.resource::<LevelIndex>().0, 1);
+    }
+
+    #[test]
+    fn ignores_goal_unlocked_elsewhere() {
+        let (mut world, _) = test_world(PlayerType::Blue);
+        unlock_goal(&mut world, Vec2::new(64.0, 0.0));
+
+        assert_eq!(events::<GoalEvent>(&world), 0);
+        assert_eq!(world.resource::<LevelIndex>().0, 0);
+    }
+
+    #[test]
+    fn records_checkpoint_on_touch() {
+        let (mut world, player) = test_world(PlayerType::Yellow);
+        world.init_resource::<RespawnPoint>();
+        world.init_resource::<Events<TriggerEntered>>();
+
+        let checkpoint = world
+            .spawn((
+                Checkpoint,
+                GlobalTransform::from_translation(Vec3::new(32.0, -16.0, 0.0)),
+            ))
+            .id();
+        world.send_event(TriggerEntered {
+            entity: player,
+            other: checkpoint,
+            normal: Vec2::Y,
+        });
+        world.run_system_once(check_checkpoint_reached);
+
+        let respawn_point = &world.resource::<RespawnPoint>().0;
+        assert_eq!(respawn_point.position, Vec2::new(32.0, -16.0));
+        assert_eq!(respawn_point.player_type, PlayerType::Yellow);
     }
 }
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::collision::CollisionLayers;
+
+// Cells are `Some(layers)` where solid
+pub type SolidGrid = Vec<Vec<Option<CollisionLayers>>>;
+
+// Greedily grows each unclaimed cell right, then down, into the largest
+// rectangle of cells sharing its layer mask
+pub fn merge_solid_cells(grid: &SolidGrid) -> Vec<(URect, CollisionLayers)> {
+    let height = grid.len();
+    let width = grid.first().map(|row| row.len()).unwrap_or_default();
+
+    let mut claimed = vec![vec![false; width]; height];
+    let mut rects = vec![];
+
+    let open = |claimed: &Vec<Vec<bool>>, x: usize, y: usize, layers: CollisionLayers| {
+        !claimed[y][x] && grid[y].get(x).copied().flatten() == Some(layers)
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let Some(layers) = grid[y].get(x).copied().flatten() else {
+                continue;
+            };
+            if claimed[y][x] {
+                continue;
+            }
+
+            let mut rect_width = 1;
+            while x + rect_width < width && open(&claimed, x + rect_width, y, layers) {
+                rect_width += 1;
+            }
+
+            let mut rect_height = 1;
+            while y + rect_height < height
+                && (x..x + rect_width).all(|cx| open(&claimed, cx, y + rect_height, layers))
+            {
+                rect_height += 1;
+            }
+
+            for row in claimed.iter_mut().skip(y).take(rect_height) {
+                for cell in row.iter_mut().skip(x).take(rect_width) {
+                    *cell = true;
+                }
+            }
+
+            rects.push((
+                URect::new(
+                    x as u32,
+                    y as u32,
+                    (x + rect_width) as u32,
+                    (y + rect_height) as u32,
+                ),
+                layers,
+            ));
+        }
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_a_floor_row_into_one_rect() {
+        let solid = Some(CollisionLayers::NEUTRAL);
+        let grid = vec![vec![None; 4], vec![solid; 4]];
+
+        assert_eq!(
+            merge_solid_cells(&grid),
+            vec![(URect::new(0, 1, 4, 2), CollisionLayers::NEUTRAL)]
+        );
+    }
+
+    #[test]
+    fn keeps_layers_apart() {
+        let blue = Some(CollisionLayers::BLUE);
+        let yellow = Some(CollisionLayers::YELLOW);
+        let grid = vec![vec![blue, blue, yellow, yellow]];
+
+        assert_eq!(
+            merge_solid_cells(&grid),
+            vec![
+                (URect::new(0, 0, 2, 1), CollisionLayers::BLUE),
+                (URect::new(2, 0, 4, 1), CollisionLayers::YELLOW),
+            ]
+        );
+    }
+}
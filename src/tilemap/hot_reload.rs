@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use super::{
+    ldtk::LevelPackAsset, level_asset::LevelAsset, level_bounds, player_spawn, spawn_tilemap,
+    tileset::TilesetAsset, validate_level, LevelBounds, LevelData, LevelReloadedEvent, Tilemap,
+};
+use crate::{
+    level::{LevelErrors, LevelState, LinkStates},
+    movement::MovementConfig,
+    platform::MovingPlatform,
+};
+
+// Platforms sit outside the tilemap, so are rebuilt separately
+type LevelRoot = Or<(With<Tilemap>, With<MovingPlatform>)>;
+
+// File changes only arrive with the `hot_reload` feature, which watches the assets folder
+#[allow(clippy::too_many_arguments)]
+pub fn reload_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    level_data: LevelData,
+    mut level_events: EventReader<AssetEvent<LevelAsset>>,
+    mut tileset_events: EventReader<AssetEvent<TilesetAsset>>,
+    tilemap_query: Query<Entity, LevelRoot>,
+    mut level_errors: ResMut<LevelErrors>,
+    mut next_state: ResMut<NextState<LevelState>>,
+    mut reload_event_writer: EventWriter<LevelReloadedEvent>,
+    link_states: Res<LinkStates>,
+) {
+    let Some(level_id) = level_data.level_handle().map(|handle| handle.id()) else {
+        return;
+    };
+
+    // Read every event so none are left over for the next frame
+    let level_modified = level_events
+        .read()
+        .filter(|event| event.is_modified(level_id))
+        .count()
+        > 0;
+    let tileset_modified = tileset_events
+        .read()
+        .filter(|event| event.is_modified(&level_data.tileset.0))
+        .count()
+        > 0;
+
+    if !level_modified && !tileset_modified {
+        return;
+    }
+
+    let (Some(tileset), Some(level)) = (level_data.tileset(), level_data.level()) else {
+        return;
+    };
+
+    let errors = validate_level(level, tileset);
+    if !errors.is_empty() {
+        let path = asset_server
+            .get_path(level_id)
+            .map(|path| path.to_string())
+            .unwrap_or_default();
+
+        level_errors.0 = errors
+            .iter()
+            .map(|error| format!("{}: {}", path, error))
+            .collect();
+        next_state.set(LevelState::Error);
+        return;
+    }
+
+    info!("Reloading level {:?}", asset_server.get_path(level_id));
+
+    for entity in &tilemap_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.insert_resource(player_spawn(level));
+    commands.insert_resource(LevelBounds(level_bounds(level)));
+    spawn_tilemap(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        tileset,
+        level,
+        &link_states,
+    );
+
+    reload_event_writer.send(LevelReloadedEvent {
+        bounds: level_bounds(level),
+    });
+}
+
+// Once a file is fixed, go back through loading and validation
+pub fn retry_after_error(
+    mut level_events: EventReader<AssetEvent<LevelAsset>>,
+    mut pack_events: EventReader<AssetEvent<LevelPackAsset>>,
+    mut tileset_events: EventReader<AssetEvent<TilesetAsset>>,
+    mut movement_events: EventReader<AssetEvent<MovementConfig>>,
+    mut level_errors: ResMut<LevelErrors>,
+    mut next_state: ResMut<NextState<LevelState>>,
+) {
+    let level_changed = level_events.read().fold(false, is_change);
+    let pack_changed = pack_events.read().fold(false, is_change);
+    let tileset_changed = tileset_events.read().fold(false, is_change);
+    let movement_changed = movement_events.read().fold(false, is_change);
+
+    if level_changed || pack_changed || tileset_changed || movement_changed {
+        level_errors.0.clear();
+        next_state.set(LevelState::Loading);
+    }
+}
+
+fn is_change<A: Asset>(changed: bool, event: &AssetEvent<A>) -> bool {
+    changed
+        || matches!(
+            event,
+            AssetEvent::Added { .. }
+                | AssetEvent::Modified { .. }
+                | AssetEvent::LoadedWithDependencies { .. }
+        )
+}
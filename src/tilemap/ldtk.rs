@@ -0,0 +1,443 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::level_asset::{LayerKind, LevelAsset, Link, PlatformPath, TileLayer};
+use crate::{collision::CollisionLayers, player::PlayerType};
+
+// Every level in an LDtk project, in project order
+#[derive(Asset, TypePath, Debug)]
+pub struct LevelPackAsset {
+    pub levels: Vec<Handle<LevelAsset>>,
+}
+
+// Subset of the LDtk project JSON that we read
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkProject {
+    defs: LdtkDefs,
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkDefs {
+    layers: Vec<LdtkLayerDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayerDef {
+    uid: i64,
+    #[serde(default)]
+    int_grid_values: Vec<LdtkIntGridValue>,
+}
+
+#[derive(Deserialize)]
+struct LdtkIntGridValue {
+    value: i64,
+    identifier: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLevel {
+    identifier: String,
+    layer_instances: Option<Vec<LdtkLayerInstance>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdtkLayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    layer_type: String,
+    #[serde(rename = "__cWid")]
+    c_wid: usize,
+    #[serde(rename = "__cHei")]
+    c_hei: usize,
+    #[serde(rename = "__gridSize")]
+    grid_size: i64,
+    layer_def_uid: i64,
+    #[serde(default)]
+    int_grid_csv: Vec<i64>,
+    #[serde(default)]
+    grid_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<LdtkTile>,
+    #[serde(default)]
+    entity_instances: Vec<LdtkEntity>,
+}
+
+#[derive(Deserialize)]
+struct LdtkTile {
+    px: [i64; 2],
+    t: usize,
+}
+
+#[derive(Deserialize)]
+struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__grid")]
+    grid: [u32; 2],
+    // In pixels
+    #[serde(default)]
+    width: i64,
+    #[serde(default)]
+    height: i64,
+    #[serde(default, rename = "fieldInstances")]
+    field_instances: Vec<LdtkField>,
+}
+
+impl LdtkEntity {
+    fn field(&self, identifier: &str) -> Option<&serde_json::Value> {
+        self.field_instances
+            .iter()
+            .find(|field| field.identifier == identifier)
+            .map(|field| &field.value)
+    }
+}
+
+#[derive(Deserialize)]
+struct LdtkField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: serde_json::Value,
+}
+
+// How Point fields are written
+#[derive(Deserialize)]
+struct LdtkPoint {
+    cx: u32,
+    cy: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum LdtkProjectLoaderError {
+    #[error("Could not read LDtk project: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse LDtk project JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Level `{0}` is saved externally, which is not supported")]
+    ExternalLevel(String),
+    #[error("Unknown layer `{0}` for IntGrid value {1}")]
+    UnknownLayer(String, i64),
+    #[error("Unknown layer `{0}` on platform in level `{1}`")]
+    UnknownPlatformLayer(String, String),
+}
+
+#[derive(Default)]
+pub struct LdtkProjectLoader;
+
+impl AssetLoader for LdtkProjectLoader {
+    type Asset = LevelPackAsset;
+    type Settings = ();
+    type Error = LdtkProjectLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let project: LdtkProject = serde_json::from_slice(&bytes)?;
+
+            let int_grid_layers = int_grid_layers(&project.defs)?;
+
+            let mut levels = vec![];
+            for level in project.levels {
+                let identifier = level.identifier.clone();
+                let level = to_level(level, &int_grid_layers)?;
+                levels.push(load_context.add_labeled_asset(identifier, level));
+            }
+
+            Ok(LevelPackAsset { levels })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+// IntGrid values are named after the collision layer they belong to
+fn int_grid_layers(
+    defs: &LdtkDefs,
+) -> Result<HashMap<(i64, i64), CollisionLayers>, LdtkProjectLoaderError> {
+    let mut int_grid_layers = HashMap::new();
+
+    for layer in &defs.layers {
+        for int_grid_value in &layer.int_grid_values {
+            let identifier = int_grid_value.identifier.clone().unwrap_or_default();
+            let Some(layers) = CollisionLayers::from_name(&identifier.to_uppercase()) else {
+                return Err(LdtkProjectLoaderError::UnknownLayer(
+                    identifier,
+                    int_grid_value.value,
+                ));
+            };
+            int_grid_layers.insert((layer.uid, int_grid_value.value), layers);
+        }
+    }
+
+    Ok(int_grid_layers)
+}
+
+fn to_level(
+    level: LdtkLevel,
+    int_grid_layers: &HashMap<(i64, i64), CollisionLayers>,
+) -> Result<LevelAsset, LdtkProjectLoaderError> {
+    let layer_instances = level
+        .layer_instances
+        .ok_or_else(|| LdtkProjectLoaderError::ExternalLevel(level.identifier.clone()))?;
+
+    // All layers share the level's cell size
+    let (width, height) = layer_instances
+        .first()
+        .map(|layer| (layer.c_wid, layer.c_hei))
+        .unwrap_or_default();
+
+    let mut layers = vec![];
+    let mut collision = vec![vec![None; width]; height];
+    let mut spawns = vec![];
+    let mut goals = vec![];
+    let mut platforms = vec![];
+    let mut links = HashMap::new();
+
+    // LDtk lists the top layer first, so walk backwards to draw bottom up
+    for layer in layer_instances.iter().rev() {
+        match layer.layer_type.as_str() {
+            // Cells outside the first layer's size are dropped, like tiles below
+            "IntGrid" if layer.c_wid > 0 => {
+                for (i, &value) in layer.int_grid_csv.iter().enumerate() {
+                    let cell = collision
+                        .get_mut(i / layer.c_wid)
+                        .and_then(|row| row.get_mut(i % layer.c_wid));
+
+                    if let (Some(cell), Some(&layers)) =
+                        (cell, int_grid_layers.get(&(layer.layer_def_uid, value)))
+                    {
+                        *cell = Some(layers);
+                    }
+                }
+            }
+            "Entities" => {
+                for entity in &layer.entity_instances {
+                    let cell = UVec2::from_array(entity.grid);
+
+                    match entity.identifier.as_str() {
+                        "Player" => {
+                            let spawn_type = entity
+                                .field("player_type")
+                                .and_then(|value| value.as_str())
+                                .and_then(PlayerType::from_name)
+                                .unwrap_or_default();
+                            spawns.push((cell, spawn_type));
+                        }
+                        "Goal" => goals.push(cell),
+                        "Platform" => platforms.push(to_platform(entity, cell, &level.identifier)?),
+                        // Links every cell the entity covers
+                        "Link" => {
+                            let link = Link {
+                                id: entity
+                                    .field("id")
+                                    .and_then(|value| value.as_u64())
+                                    .unwrap_or_default() as u32,
+                                inverted: entity
+                                    .field("inverted")
+                                    .and_then(|value| value.as_bool())
+                                    .unwrap_or_default(),
+                                layers: entity
+                                    .field("layer")
+                                    .and_then(|value| value.as_str())
+                                    .and_then(|name| {
+                                        CollisionLayers::from_name(&name.to_uppercase())
+                                    }),
+                            };
+                            let size = UVec2::new(
+                                (entity.width / layer.grid_size).max(1) as u32,
+                                (entity.height / layer.grid_size).max(1) as u32,
+                            );
+
+                            for y in 0..size.y {
+                                for x in 0..size.x {
+                                    links.insert(cell + UVec2::new(x, y), link);
+                                }
+                            }
+                        }
+                        other => warn!("Skipping unknown LDtk entity `{}`", other),
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // Tile and auto-layer tiles are what actually gets drawn
+        if layer.grid_tiles.is_empty() && layer.auto_layer_tiles.is_empty() {
+            continue;
+        }
+
+        let mut rows = vec![vec![None; width]; height];
+        for tile in layer.grid_tiles.iter().chain(&layer.auto_layer_tiles) {
+            let x = (tile.px[0] / layer.grid_size) as usize;
+            let y = (tile.px[1] / layer.grid_size) as usize;
+
+            if let Some(cell) = rows.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *cell = Some(tile.t);
+            }
+        }
+
+        layers.push(TileLayer {
+            name: layer.identifier.clone(),
+            kind: LayerKind::from_layer_name(&layer.identifier),
+            rows,
+        });
+    }
+
+    let mut level = LevelAsset::new(layers);
+    level.collision = Some(collision);
+    level.goals = goals;
+    level.platforms = platforms;
+    level.links = links;
+    level.spawns = spawns;
+
+    Ok(level)
+}
+
+// Starts where the entity is placed, then follows its `path` Point array
+fn to_platform(
+    entity: &LdtkEntity,
+    cell: UVec2,
+    level_identifier: &str,
+) -> Result<PlatformPath, LdtkProjectLoaderError> {
+    let path = entity
+        .field("path")
+        .cloned()
+        .and_then(|value| serde_json::from_value::<Vec<LdtkPoint>>(value).ok())
+        .unwrap_or_default();
+
+    let waypoints = [cell]
+        .into_iter()
+        .chain(path.iter().map(|point| UVec2::new(point.cx, point.cy)))
+        .collect();
+
+    let mut platform = PlatformPath::new(waypoints);
+
+    if let Some(value) = entity.field("layer").filter(|value| !value.is_null()) {
+        platform.layers = value
+            .as_str()
+            .and_then(|name| CollisionLayers::from_name(&name.to_uppercase()))
+            .ok_or_else(|| {
+                LdtkProjectLoaderError::UnknownPlatformLayer(
+                    value.to_string(),
+                    level_identifier.to_string(),
+                )
+            })?;
+    }
+    if let Some(width) = entity.field("width").and_then(|value| value.as_u64()) {
+        platform.width = width as u32;
+    }
+    if let Some(speed) = entity.field("speed").and_then(|value| value.as_f64()) {
+        platform.speed = speed as f32;
+    }
+
+    Ok(platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_levels() -> Vec<LevelAsset> {
+        let bytes = include_bytes!("../../assets/levels/samples/levels.ldtk");
+        let project: LdtkProject = serde_json::from_slice(bytes).unwrap();
+        let int_grid_layers = int_grid_layers(&project.defs).unwrap();
+
+        project
+            .levels
+            .into_iter()
+            .map(|level| to_level(level, &int_grid_layers).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reads_every_level_in_order() {
+        let levels = sample_levels();
+
+        assert_eq!(levels.len(), 3);
+        for level in &levels {
+            assert_eq!(level.size(), UVec2::new(20, 9));
+            assert_eq!(level.spawns, vec![(UVec2::new(4, 3), PlayerType::Blue)]);
+        }
+        assert_eq!(levels[0].goals, vec![UVec2::new(16, 5)]);
+        assert_eq!(levels[1].goals, vec![UVec2::new(12, 1)]);
+        assert_eq!(levels[2].goals, vec![UVec2::new(16, 5)]);
+    }
+
+    #[test]
+    fn reads_tile_layer() {
+        let levels = sample_levels();
+
+        assert_eq!(levels[0].layers.len(), 1);
+        let layer = &levels[0].layers[0];
+        assert_eq!(layer.name, "Tiles");
+        assert_eq!(layer.kind, LayerKind::Collision);
+        assert_eq!(layer.rows[0][0], Some(21));
+        assert_eq!(layer.rows[0][3], None);
+    }
+
+    #[test]
+    fn maps_int_grid_onto_collision_layers() {
+        let levels = sample_levels();
+        let collision = levels[0].collision.as_ref().unwrap();
+
+        assert_eq!(collision[0][0], Some(CollisionLayers::NEUTRAL));
+        assert_eq!(collision[0][3], None);
+        assert_eq!(collision[4][13], Some(CollisionLayers::BLUE));
+        assert_eq!(collision[5][11], Some(CollisionLayers::YELLOW));
+    }
+
+    #[test]
+    fn drops_int_grid_cells_outside_the_level() {
+        // The IntGrid layer is wider and taller than the level's first layer
+        let level: LdtkLevel = serde_json::from_value(serde_json::json!({
+            "identifier": "Level_0",
+            "layerInstances": [
+                {
+                    "__identifier": "Entities",
+                    "__type": "Entities",
+                    "__cWid": 2,
+                    "__cHei": 1,
+                    "__gridSize": 16,
+                    "layerDefUid": 3
+                },
+                {
+                    "__identifier": "Collision",
+                    "__type": "IntGrid",
+                    "__cWid": 3,
+                    "__cHei": 2,
+                    "__gridSize": 16,
+                    "layerDefUid": 1,
+                    "intGridCsv": [1, 1, 1, 1, 1, 1]
+                }
+            ]
+        }))
+        .unwrap();
+        let int_grid_layers = HashMap::from([((1, 1), CollisionLayers::NEUTRAL)]);
+
+        let level = to_level(level, &int_grid_layers).unwrap();
+        assert_eq!(
+            level.collision,
+            Some(vec![vec![Some(CollisionLayers::NEUTRAL); 2]])
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use thiserror::Error;
+
+use crate::{collision::CollisionLayers, player::PlayerType};
+
+const EMPTY_MARKER: &str = "x";
+const SPAWN_MARKER: &str = "S";
+const LINK_MARKER: &str = "@";
+const INVERTED_MARKER: &str = "!";
+const LAYER_MARKER: &str = ":";
+const PLATFORMS_HEADER: &str = "platforms";
+
+pub const DEFAULT_PLATFORM_WIDTH: u32 = 2;
+// Cells per second
+pub const DEFAULT_PLATFORM_SPEED: f32 = 2.0;
+
+pub type TileGrid = Vec<Vec<Option<usize>>>;
+
+// Only collision layers spawn colliders, the others are just drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LayerKind {
+    Background,
+    Collision,
+    Foreground,
+}
+
+impl LayerKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "background" => Some(LayerKind::Background),
+            "collision" => Some(LayerKind::Collision),
+            "foreground" => Some(LayerKind::Foreground),
+            _ => None,
+        }
+    }
+
+    // Editors name layers freely, so only look at how the name starts
+    pub fn from_layer_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if name.starts_with("background") {
+            LayerKind::Background
+        } else if name.starts_with("foreground") {
+            LayerKind::Foreground
+        } else {
+            LayerKind::Collision
+        }
+    }
+}
+
+// A platform that travels through its waypoints in order, then back to the first.
+// Waypoints are the cell of its leftmost tile
+#[derive(Clone, Debug)]
+pub struct PlatformPath {
+    pub waypoints: Vec<UVec2>,
+    // In cells
+    pub width: u32,
+    pub layers: CollisionLayers,
+    // Cells per second
+    pub speed: f32,
+}
+
+impl PlatformPath {
+    pub fn new(waypoints: Vec<UVec2>) -> Self {
+        PlatformPath {
+            waypoints,
+            width: DEFAULT_PLATFORM_WIDTH,
+            layers: CollisionLayers::NEUTRAL,
+            speed: DEFAULT_PLATFORM_SPEED,
+        }
+    }
+}
+
+// Ties a cell's tile to the levers, pressure plates and keys sharing its id.
+// Linked blocks are solid while their id is off, or while on when inverted,
+// and linked goals are locked while it is off
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    pub id: u32,
+    pub inverted: bool,
+    // Replaces the tile's collision layers, e.g. for a key only one color can take
+    pub layers: Option<CollisionLayers>,
+}
+
+// Invisible triggers placed as editor objects rather than tiles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerKind {
+    Hazard,
+    Checkpoint,
+}
+
+#[derive(Debug)]
+pub struct TileLayer {
+    pub name: String,
+    pub kind: LayerKind,
+    pub rows: TileGrid,
+}
+
+// A level is a stack of grids of tile atlas indices, `None` for empty cells
+#[derive(Asset, TypePath, Debug)]
+pub struct LevelAsset {
+    // Drawn in order within each kind
+    pub layers: Vec<TileLayer>,
+    // Validation expects exactly one
+    pub spawns: Vec<(UVec2, PlayerType)>,
+    // Goal cells placed as objects rather than door tiles
+    pub goals: Vec<UVec2>,
+    pub platforms: Vec<PlatformPath>,
+    // Switches and the blocks they open or close, by cell
+    pub links: HashMap<UVec2, Link>,
+    // Hazard and checkpoint cells without a tile of their own
+    pub triggers: HashMap<UVec2, TriggerKind>,
+    // Per atlas index collision layer overrides
+    pub tile_layers: HashMap<usize, CollisionLayers>,
+    // Per cell collision layers, replacing tile solidity when present
+    pub collision: Option<Vec<Vec<Option<CollisionLayers>>>>,
+    // Cells that could not be parsed, with their layer and original text
+    pub invalid_cells: Vec<(String, UVec2, String)>,
+}
+
+impl LevelAsset {
+    pub fn new(layers: Vec<TileLayer>) -> Self {
+        LevelAsset {
+            layers,
+            spawns: vec![],
+            goals: vec![],
+            platforms: vec![],
+            links: HashMap::new(),
+            triggers: HashMap::new(),
+            tile_layers: HashMap::new(),
+            collision: None,
+            invalid_cells: vec![],
+        }
+    }
+
+    // Only valid once the level has passed validation
+    pub fn spawn(&self) -> (UVec2, PlayerType) {
+        self.spawns[0]
+    }
+
+    // Size in cells, taken from the first layer
+    pub fn size(&self) -> UVec2 {
+        let rows = self.layers.first().map(|layer| &layer.rows);
+        let width = rows
+            .and_then(|rows| rows.first())
+            .map(|row| row.len())
+            .unwrap_or_default();
+        let height = rows.map(|rows| rows.len()).unwrap_or_default();
+
+        UVec2::new(width as u32, height as u32)
+    }
+
+    // Comma-separated tile indices per row, `x` for empty cells
+    // `S` marks the spawn, optionally with a type like `S:yellow`
+    // `index@id` links a tile to an id, `index@!id` inverts the link,
+    // and a `:layer` suffix like `27@1:blue` puts the tile on that layer
+    // `[background]`, `[collision]` or `[foreground]` starts a new layer,
+    // files without one are a single collision layer
+    // `[platforms]` lists one moving platform per row as
+    // `layer, width, speed, x y, x y, ...` with 0-based waypoint cells
+    pub fn parse(text: &str) -> Result<Self, LevelAssetLoaderError> {
+        let mut layers = vec![];
+        let mut spawns = vec![];
+        let mut platforms = vec![];
+        let mut links = HashMap::new();
+        let mut invalid_cells = vec![];
+        let mut in_platforms = false;
+
+        let mut name = "collision".to_string();
+        let mut rows: TileGrid = vec![];
+
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix("[").and_then(|l| l.strip_suffix("]")) {
+                if !rows.is_empty() {
+                    layers.push(to_layer(name, rows)?);
+                }
+                in_platforms = header.trim() == PLATFORMS_HEADER;
+                name = header.trim().to_string();
+                rows = vec![];
+                continue;
+            }
+
+            if in_platforms {
+                platforms.push(parse_platform(line)?);
+                continue;
+            }
+
+            let y = rows.len();
+            let row = line
+                .split(",")
+                .map(|c| c.trim())
+                .enumerate()
+                .map(|(x, c)| {
+                    let cell = UVec2::new(x as u32, y as u32);
+
+                    if let Ok(index) = c.parse::<usize>() {
+                        return Some(index);
+                    }
+
+                    if let Some((index, link)) = c.split_once(LINK_MARKER) {
+                        if let (Ok(index), Some(link)) = (index.parse::<usize>(), parse_link(link))
+                        {
+                            links.insert(cell, link);
+                            return Some(index);
+                        }
+                    }
+
+                    if let Some(spawn_type) = c.strip_prefix(SPAWN_MARKER) {
+                        match spawn_type.strip_prefix(":").map(PlayerType::from_name) {
+                            None if spawn_type.is_empty() => {
+                                spawns.push((cell, PlayerType::default()))
+                            }
+                            Some(Some(spawn_type)) => spawns.push((cell, spawn_type)),
+                            _ => invalid_cells.push((name.clone(), cell, c.to_string())),
+                        }
+                    } else if c != EMPTY_MARKER {
+                        invalid_cells.push((name.clone(), cell, c.to_string()));
+                    }
+
+                    None
+                })
+                .collect();
+
+            rows.push(row);
+        }
+        if !in_platforms {
+            layers.push(to_layer(name, rows)?);
+        }
+
+        let mut level = LevelAsset::new(layers);
+        level.spawns = spawns;
+        level.platforms = platforms;
+        level.links = links;
+        level.invalid_cells = invalid_cells;
+
+        Ok(level)
+    }
+}
+
+fn parse_link(text: &str) -> Option<Link> {
+    let (text, layers) = match text.split_once(LAYER_MARKER) {
+        Some((text, name)) => (
+            text,
+            Some(CollisionLayers::from_name(&name.to_uppercase())?),
+        ),
+        None => (text, None),
+    };
+    let (inverted, id) = match text.strip_prefix(INVERTED_MARKER) {
+        Some(id) => (true, id),
+        None => (false, text),
+    };
+
+    Some(Link {
+        id: id.parse().ok()?,
+        inverted,
+        layers,
+    })
+}
+
+fn parse_platform(line: &str) -> Result<PlatformPath, LevelAssetLoaderError> {
+    let invalid = || LevelAssetLoaderError::InvalidPlatform(line.to_string());
+    let fields = line
+        .split(",")
+        .map(|field| field.trim())
+        .collect::<Vec<&str>>();
+
+    let [layers, width, speed, waypoints @ ..] = fields.as_slice() else {
+        return Err(invalid());
+    };
+
+    let waypoints = waypoints
+        .iter()
+        .map(|waypoint| {
+            let cell = waypoint
+                .split_whitespace()
+                .map(|c| c.parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .ok()?;
+            match cell.as_slice() {
+                &[x, y] => Some(UVec2::new(x, y)),
+                _ => None,
+            }
+        })
+        .collect::<Option<Vec<UVec2>>>()
+        .filter(|waypoints| !waypoints.is_empty())
+        .ok_or_else(invalid)?;
+
+    Ok(PlatformPath {
+        waypoints,
+        width: width.parse().map_err(|_| invalid())?,
+        layers: CollisionLayers::from_name(&layers.to_uppercase()).ok_or_else(invalid)?,
+        speed: speed.parse().map_err(|_| invalid())?,
+    })
+}
+
+fn to_layer(name: String, rows: TileGrid) -> Result<TileLayer, LevelAssetLoaderError> {
+    let kind = LayerKind::from_name(&name)
+        .ok_or_else(|| LevelAssetLoaderError::UnknownLayer(name.clone()))?;
+
+    Ok(TileLayer { name, kind, rows })
+}
+
+#[derive(Debug, Error)]
+pub enum LevelAssetLoaderError {
+    #[error("Could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Level file is not valid UTF-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Unknown layer `[{0}]`, expected background, collision, foreground or platforms")]
+    UnknownLayer(String),
+    #[error("Platform `{0}` should be `layer, width, speed, x y, ...`")]
+    InvalidPlatform(String),
+}
+
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = LevelAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            LevelAsset::parse(&String::from_utf8(bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv"]
+    }
+}
@@ -1,32 +0,0 @@
-pub const LEVEL_0: &'static str =
-" 21,  22,  23,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,  21,  22,  23
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x,  93,  94,   x, 110, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   9,   x, 113, 114,   x, 130, 121, 122, 123
-121, 122, 123,  21,  22,  22,  22,  22,  22,  22,  22,  22,  22,  22,  22,  22,  23, 121, 122, 123
-121, 122, 123, 121, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 123, 121, 122, 123
-141, 142, 143, 141, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 143, 141, 142, 143";
-
-pub const LEVEL_1: &'static str =
-" 61,  62,  63,   x,   x,   x,   x,   x,   x,   x,   x,   x, 110,   x,   x,   x,   x,  61,  62,  63
-121, 122, 123,   x,   x,   x,   x,   x,   x,   x,   x,   x, 130,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   x,  93,  94,  93,  94,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   x,   9, 113, 114, 113, 114,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   x,   9,   x,   x,   x,   x,   x,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   9,   x,   x,   x,   x,   x,   x,   x,   x,   x,   x, 121, 122, 123
-121, 122, 123,  61,  62,  62,  62,  62,  62,  62,  62,  62,  62,  62,  62,  62,  63, 121, 122, 123
-121, 122, 123, 121, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 123, 121, 122, 123
-141, 142, 143, 141, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 143, 141, 142, 143";
-
-pub const LEVEL_2: &'static str =
-"101, 102, 103,   x,   x,   x,   9,   x,   x,  95,   x,   x,   9,   x,   x,  95,   x, 101, 102, 103
-121, 122, 123,   x,   x,   x,   9,   x,   x, 115,   x,   x,   9,   x,   x, 115,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   9,   x,   x, 115,   x,   x,   9,   x,   x, 115,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   9,   x,   x, 115,   x,   x,   9,   x,   x, 115,   x, 121, 122, 123
-121, 122, 123,   x,   x,   x,   9,   x,   x, 115,   x,   x,   9,   x,   x, 115, 110, 121, 122, 123
-121, 122, 123,   x,   x,   x,   9,   x,   x, 135,   x,   x,   9,   x,   x, 135, 130, 121, 122, 123
-121, 122, 123, 101, 102, 102, 102, 102, 102, 102, 102, 102, 102, 102, 102, 102, 103, 121, 122, 123
-121, 122, 123, 121, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 122, 123, 121, 122, 123
-141, 142, 143, 141, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 142, 143, 141, 142, 143";
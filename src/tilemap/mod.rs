@@ -1,45 +1,277 @@
 use crate::{
-    collision::Collider,
-    level::{Despawnable, Goal, LevelIndex, LevelState},
+    collision::{Collider, CollisionLayers, MovingCollider},
+    level::{
+        Checkpoint, Despawnable, Goal, Hazard, LevelCount, LevelErrors, LevelIndex, LevelState,
+        LinkStates,
+    },
+    links::{key_color, Key, LinkSwitch, LinkedBlock, Lock, Locked, SwitchKind},
+    movement::MovementConfigHandle,
+    platform::MovingPlatform,
+    player::PlayerSpawn,
+};
+use bevy::{
+    asset::{RecursiveDependencyLoadState, UntypedAssetId},
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::HashMap,
+};
+
+use self::{
+    colliders::merge_solid_cells,
+    ldtk::{LdtkProjectLoader, LevelPackAsset},
+    level_asset::{LayerKind, LevelAsset, LevelAssetLoader, PlatformPath, TriggerKind},
+    tiled::TiledMapLoader,
+    tileset::{TilesetAsset, TilesetAssetLoader},
+    validation::validate_level,
 };
-use bevy::{prelude::*, utils::HashMap};
 
-use self::level_maps::*;
+mod colliders;
+mod hot_reload;
+mod ldtk;
+mod level_asset;
+mod tiled;
+mod tileset;
+mod validation;
 
-mod level_maps;
+const TILESET_PATH: &str = "tilemap_packed.tileset.ron";
 
-const YELLOW_BLOCKS: [usize; 1] = [9];
-const BLUE_BLOCKS: [usize; 10] = [93, 94, 95, 113, 114, 115, 132, 133, 134, 135];
+// Single levels (.csv, .tmj) or level packs (.ldtk), played in order
+const LEVEL_PATHS: [&str; 3] = [
+    "levels/level0.csv",
+    "levels/level1.csv",
+    "levels/level2.csv",
+];
 
 const TILE_SIZE: f32 = 16.0;
+
+const TILEMAP_Z: f32 = -1.0;
+// Relative to the tilemap, so the player at z = 0 sits between
+// the collision and foreground layers
+const BACKGROUND_Z: f32 = -1.0;
+const COLLISION_Z: f32 = 0.0;
+const FOREGROUND_Z: f32 = 2.0;
+const LAYER_Z_STEP: f32 = 0.01;
 const GOAL_COLLIDER_SIZE: f32 = 1.0;
+// A little smaller than a tile so grazing spikes isn't fatal
+const HAZARD_COLLIDER_SIZE: f32 = 12.0;
 
 #[derive(Component)]
 pub struct Tilemap;
 
+// Spawns the tilemap on entering LevelState::Init, or rebuilds it on reload
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadLevelSet;
+
 #[derive(Component)]
 pub struct Tile;
 
+// World space area covered by the current level's cells
+#[derive(Resource, Default)]
+pub struct LevelBounds(pub Rect);
+
+// Sent after the current level's file changed and its tilemap was rebuilt
+#[derive(Event)]
+pub struct LevelReloadedEvent {
+    pub bounds: Rect,
+}
+
+enum LevelSource {
+    Level(Handle<LevelAsset>),
+    Pack(Handle<LevelPackAsset>),
+}
+
+impl LevelSource {
+    fn id(&self) -> UntypedAssetId {
+        match self {
+            LevelSource::Level(handle) => handle.id().untyped(),
+            LevelSource::Pack(handle) => handle.id().untyped(),
+        }
+    }
+}
+
 #[derive(Resource)]
-struct Levels(HashMap<&'static str, &'static str>);
+struct LevelSources(Vec<LevelSource>);
+
+// Flattened from LevelSources once everything is loaded
+#[derive(Resource, Default)]
+struct Levels(Vec<Handle<LevelAsset>>);
+
+#[derive(Resource)]
+struct Tileset(Handle<TilesetAsset>);
+
+// Everything LevelState::Loading waits on
+#[derive(SystemParam)]
+struct LevelLoading<'w> {
+    asset_server: Res<'w, AssetServer>,
+    sources: Res<'w, LevelSources>,
+    tileset: Res<'w, Tileset>,
+    movement_config: Res<'w, MovementConfigHandle>,
+    level_packs: Res<'w, Assets<LevelPackAsset>>,
+    level_assets: Res<'w, Assets<LevelAsset>>,
+    tileset_assets: Res<'w, Assets<TilesetAsset>>,
+}
+
+impl<'w> LevelLoading<'w> {
+    fn ids(&self) -> impl Iterator<Item = UntypedAssetId> + '_ {
+        self.sources.0.iter().map(|source| source.id()).chain([
+            self.tileset.0.id().untyped(),
+            self.movement_config.0.id().untyped(),
+        ])
+    }
+
+    fn path(&self, id: impl Into<UntypedAssetId>) -> String {
+        self.asset_server
+            .get_path(id)
+            .map(|path| path.to_string())
+            .unwrap_or_default()
+    }
+}
+
+// Loaded level + tileset assets for the current LevelIndex
+#[derive(SystemParam)]
+struct LevelData<'w> {
+    levels: Res<'w, Levels>,
+    level_assets: Res<'w, Assets<LevelAsset>>,
+    tileset: Res<'w, Tileset>,
+    tileset_assets: Res<'w, Assets<TilesetAsset>>,
+    level_index: Res<'w, LevelIndex>,
+}
+
+impl<'w> LevelData<'w> {
+    fn level_handle(&self) -> Option<&Handle<LevelAsset>> {
+        self.levels.0.get(self.level_index.0 as usize)
+    }
+
+    fn level(&self) -> Option<&LevelAsset> {
+        self.level_handle()
+            .and_then(|handle| self.level_assets.get(handle))
+    }
+
+    fn tileset(&self) -> Option<&TilesetAsset> {
+        self.tileset_assets.get(&self.tileset.0)
+    }
+}
 
 pub struct TilemapPlugin;
 
 impl Plugin for TilemapPlugin {
     fn build(&self, app: &mut App) {
-        // TODO: in future, figure out how to do file reading
-        // Cause normal fs operations don't work in WASM
-        let levels = vec![
-            ("level0", LEVEL_0),
-            ("level1", LEVEL_1),
-            ("level2", LEVEL_2),
-        ]
-        .into_iter()
-        .collect::<HashMap<&str, &str>>();
-
         app.insert_resource(Msaa::Off)
-            .insert_resource(Levels(levels))
-            .add_systems(OnEnter(LevelState::Init), load_level);
+            .init_asset::<LevelAsset>()
+            .init_asset_loader::<LevelAssetLoader>()
+            .init_asset_loader::<TiledMapLoader>()
+            .init_asset::<LevelPackAsset>()
+            .init_asset_loader::<LdtkProjectLoader>()
+            .init_asset::<TilesetAsset>()
+            .init_asset_loader::<TilesetAssetLoader>()
+            .add_systems(Startup, load_level_assets)
+            .add_systems(
+                Update,
+                check_level_assets_loaded.run_if(in_state(LevelState::Loading)),
+            )
+            .add_event::<LevelReloadedEvent>()
+            .init_resource::<LevelBounds>()
+            .add_systems(OnEnter(LevelState::Init), load_level.in_set(LoadLevelSet))
+            .add_systems(
+                Update,
+                (
+                    hot_reload::reload_level
+                        .in_set(LoadLevelSet)
+                        .run_if(in_state(LevelState::Play)),
+                    hot_reload::retry_after_error.run_if(in_state(LevelState::Error)),
+                ),
+            );
+    }
+}
+
+// Levels go through the AssetServer so this works in WASM too
+fn load_level_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sources = LEVEL_PATHS
+        .iter()
+        .map(|&path| match path.ends_with(".ldtk") {
+            true => LevelSource::Pack(asset_server.load(path)),
+            false => LevelSource::Level(asset_server.load(path)),
+        })
+        .collect();
+
+    commands.insert_resource(LevelSources(sources));
+    commands.insert_resource(Levels::default());
+    commands.insert_resource(Tileset(asset_server.load(TILESET_PATH)));
+}
+
+fn check_level_assets_loaded(
+    loading: LevelLoading,
+    mut levels: ResMut<Levels>,
+    mut level_count: ResMut<LevelCount>,
+    mut level_errors: ResMut<LevelErrors>,
+    mut next_state: ResMut<NextState<LevelState>>,
+) {
+    // The AssetServer logs the underlying error
+    let failed = loading
+        .ids()
+        .filter(|&id| {
+            loading.asset_server.recursive_dependency_load_state(id)
+                == RecursiveDependencyLoadState::Failed
+        })
+        .map(|id| format!("{}: could not be loaded", loading.path(id)))
+        .collect::<Vec<String>>();
+
+    if !failed.is_empty() {
+        level_errors.0 = failed;
+        next_state.set(LevelState::Error);
+        return;
+    }
+
+    if !loading
+        .ids()
+        .all(|id| loading.asset_server.is_loaded_with_dependencies(id))
+    {
+        return;
+    }
+
+    levels.0 = loading
+        .sources
+        .0
+        .iter()
+        .flat_map(|source| match source {
+            LevelSource::Level(handle) => vec![handle.clone()],
+            LevelSource::Pack(handle) => loading
+                .level_packs
+                .get(handle)
+                .map(|pack| pack.levels.clone())
+                .unwrap_or_default(),
+        })
+        .collect();
+    level_count.0 = levels.0.len() as u8;
+
+    // e.g. an LDtk project without any levels
+    if levels.0.is_empty() {
+        level_errors.0 = vec![format!("No levels found in {}", LEVEL_PATHS.join(", "))];
+        next_state.set(LevelState::Error);
+        return;
+    }
+
+    let tileset = loading
+        .tileset_assets
+        .get(&loading.tileset.0)
+        .expect("No tileset found");
+
+    for handle in &levels.0 {
+        let Some(level) = loading.level_assets.get(handle) else {
+            continue;
+        };
+
+        for error in validate_level(level, tileset) {
+            level_errors
+                .0
+                .push(format!("{}: {}", loading.path(handle), error));
+        }
+    }
+
+    if level_errors.0.is_empty() {
+        next_state.set(LevelState::Init);
+    } else {
+        next_state.set(LevelState::Error);
     }
 }
 
@@ -47,18 +279,82 @@ fn load_level(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    levels: Res<Levels>,
-    level_index: Res<LevelIndex>,
+    level_data: LevelData,
+    mut level_errors: ResMut<LevelErrors>,
+    mut next_state: ResMut<NextState<LevelState>>,
+    link_states: Res<LinkStates>,
+) {
+    // Loading checked both, but a pack can lose levels when it is reloaded
+    let (Some(tileset), Some(level)) = (level_data.tileset(), level_data.level()) else {
+        level_errors.0 = vec![format!("Level {} not found", level_data.level_index.0 + 1)];
+        next_state.set(LevelState::Error);
+        return;
+    };
+
+    commands.insert_resource(player_spawn(level));
+    commands.insert_resource(LevelBounds(level_bounds(level)));
+    spawn_tilemap(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        tileset,
+        level,
+        &link_states,
+    );
+}
+
+fn player_spawn(level: &LevelAsset) -> PlayerSpawn {
+    let (spawn, spawn_type) = level.spawn();
+
+    PlayerSpawn {
+        position: Vec2::new(spawn.x as f32 * TILE_SIZE, spawn.y as f32 * -TILE_SIZE),
+        player_type: spawn_type,
+    }
+}
+
+// World space area covered by the level's cells
+fn level_bounds(level: &LevelAsset) -> Rect {
+    let size = level.size().as_vec2();
+
+    Rect::new(
+        -TILE_SIZE / 2.0,
+        TILE_SIZE / 2.0,
+        (size.x - 0.5) * TILE_SIZE,
+        -(size.y - 0.5) * TILE_SIZE,
+    )
+}
+
+fn layer_z(kind: LayerKind) -> f32 {
+    match kind {
+        LayerKind::Background => BACKGROUND_Z,
+        LayerKind::Collision => COLLISION_Z,
+        LayerKind::Foreground => FOREGROUND_Z,
+    }
+}
+
+fn spawn_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    tileset: &TilesetAsset,
+    level: &LevelAsset,
+    link_states: &LinkStates,
 ) {
-    let texture = asset_server.load("tilemap_packed.png");
-    let layout = TextureAtlasLayout::from_grid(Vec2::splat(18.), 20, 9, None, None);
+    let texture = asset_server.load(&tileset.image);
+    let layout = TextureAtlasLayout::from_grid(
+        Vec2::splat(tileset.tile_size),
+        tileset.columns,
+        tileset.rows,
+        None,
+        None,
+    );
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 
     // Store texture + layout on tilemap strongly, children inherit weak
     let tilemap_entity = commands
         .spawn((
             SpatialBundle {
-                transform: Transform::from_xyz(-4.0 * TILE_SIZE, 3.0 * TILE_SIZE, -1.0),
+                transform: Transform::from_xyz(0.0, 0.0, TILEMAP_Z),
                 visibility: Visibility::Hidden,
                 ..default()
             },
@@ -70,37 +366,56 @@ fn load_level(
         .id();
 
     let mut tile_entities = vec![];
+    // Where linked goals are, to block them off below
+    let mut goal_locks = vec![];
 
-    let level = levels
-        .0
-        .get(format!("level{}", level_index.0).as_str())
-        .expect("No level found");
-
-    for (y, line) in level.lines().enumerate() {
-        for (x, c) in line.split(",").map(|c| c.trim()).enumerate() {
-            let x_pos = x as f32 * 16.0;
-            let y_pos = y as f32 * -16.0;
-
-            if let Ok(index) = c.parse::<usize>() {
-                let layer_mask = if YELLOW_BLOCKS.contains(&index) {
-                    0b100
-                } else if BLUE_BLOCKS.contains(&index) {
-                    0b10
-                } else {
-                    1
+    // Solid tiles are gathered here and merged into larger colliders below
+    let size = level.size();
+    let mut solid_cells = level
+        .collision
+        .clone()
+        .unwrap_or_else(|| vec![vec![None; size.x as usize]; size.y as usize]);
+
+    // Same-kind layers stack in file order
+    let mut layer_counts = HashMap::new();
+
+    for layer in &level.layers {
+        let count = layer_counts.entry(layer.kind).or_insert(0);
+        let z_pos = layer_z(layer.kind) + *count as f32 * LAYER_Z_STEP;
+        *count += 1;
+
+        for (y, row) in layer.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let x_pos = x as f32 * TILE_SIZE;
+                let y_pos = y as f32 * -TILE_SIZE;
+
+                let &Some(index) = cell else {
+                    continue;
                 };
+                let properties = tileset.get(index).expect("Unknown tile");
+
+                // Decoration layers never collide, and a collision grid
+                // takes over solidity from the tiles
+                let collides = layer.kind == LayerKind::Collision;
+                let link = level.links.get(&UVec2::new(x as u32, y as u32));
+
+                // Keys picked up before a hot reload stay picked up
+                if collides && properties.key && link.is_some_and(|link| link_states.is_on(link.id))
+                {
+                    continue;
+                }
 
-                // Should be the door
-                let is_trigger = index == 110 || index == 130;
-                let size = if is_trigger {
+                let trigger_size = if properties.goal {
                     GOAL_COLLIDER_SIZE
+                } else if properties.hazard {
+                    HAZARD_COLLIDER_SIZE
                 } else {
                     TILE_SIZE
                 };
 
                 let mut tile_entity = commands.spawn((
                     SpriteBundle {
-                        transform: Transform::from_xyz(x_pos, y_pos, 0.0)
+                        transform: Transform::from_xyz(x_pos, y_pos, z_pos)
                             .with_scale(Vec3::splat(8.0 / 9.0)),
                         texture: texture.clone_weak(),
                         visibility: Visibility::Visible,
@@ -110,24 +425,282 @@ fn load_level(
                         layout: texture_atlas_layout.clone_weak(),
                         index,
                     },
-                    Collider {
-                        width: size,
-                        height: size,
-                        layer_mask,
-                        is_trigger,
-                    },
+                    Tile,
                 ));
 
-                if is_trigger {
+                let layers = link
+                    .and_then(|link| link.layers)
+                    .or(level.tile_layers.get(&index).copied())
+                    .unwrap_or(properties.layers);
+
+                if collides && properties.trigger {
+                    tile_entity.insert(Collider {
+                        width: trigger_size,
+                        height: trigger_size,
+                        membership: layers,
+                        filter: CollisionLayers::empty(),
+                        is_trigger: true,
+                        one_way: false,
+                    });
+                } else if collides && properties.solid && properties.one_way {
+                    // Left unmerged, seams don't matter when only landing on top
+                    tile_entity.insert(Collider {
+                        width: TILE_SIZE,
+                        height: TILE_SIZE,
+                        membership: layers,
+                        filter: CollisionLayers::empty(),
+                        is_trigger: false,
+                        one_way: true,
+                    });
+                } else if let Some(link) = link.filter(|_| collides && properties.solid) {
+                    // Left unmerged so it can open and close on its own
+                    let block_layers = match level.collision {
+                        Some(_) => solid_cells[y][x].take(),
+                        None => Some(layers),
+                    };
+
+                    if let Some(block_layers) = block_layers {
+                        tile_entity.insert((
+                            Collider {
+                                width: TILE_SIZE,
+                                height: TILE_SIZE,
+                                membership: block_layers,
+                                filter: CollisionLayers::empty(),
+                                is_trigger: false,
+                                one_way: false,
+                            },
+                            LinkedBlock {
+                                id: link.id,
+                                inverted: link.inverted,
+                                layers: block_layers,
+                            },
+                        ));
+                    }
+                } else if collides && properties.solid && level.collision.is_none() {
+                    solid_cells[y][x] = Some(layers);
+                }
+
+                let switch_kind = match (properties.plate, properties.lever) {
+                    (true, _) => Some(SwitchKind::Plate),
+                    (_, true) => Some(SwitchKind::Lever),
+                    _ => None,
+                };
+                if let (Some(kind), Some(link)) = (switch_kind.filter(|_| collides), link) {
+                    tile_entity.insert(LinkSwitch { id: link.id, kind });
+                }
+
+                if let Some(link) = link.filter(|_| collides && properties.key) {
+                    tile_entity.insert((
+                        Key { id: link.id },
+                        Sprite {
+                            color: key_color(layers),
+                            ..default()
+                        },
+                    ));
+                }
+
+                // Locked until apply_link_states has looked at it
+                if let Some(link) = link.filter(|_| collides && properties.goal) {
+                    tile_entity.insert((Lock { id: link.id }, Locked));
+                    goal_locks.push((Vec2::new(x_pos, y_pos), link.id));
+                }
+
+                if collides && properties.goal {
                     tile_entity.insert(Goal);
                 }
 
+                if collides && properties.hazard {
+                    tile_entity.insert(Hazard);
+                }
+
+                if collides && properties.checkpoint {
+                    tile_entity.insert(Checkpoint);
+                }
+
                 tile_entities.push(tile_entity.id());
             }
         }
     }
 
+    // Fewer, larger colliders, with no seams to snag on along floors and walls
+    for (rect, layers) in merge_solid_cells(&solid_cells) {
+        let rect_size = rect.size().as_vec2() * TILE_SIZE;
+        // Cell positions are tile centers, so offset from the first cell's center
+        let center = Vec2::new(
+            rect.min.x as f32 * TILE_SIZE + (rect_size.x - TILE_SIZE) / 2.0,
+            rect.min.y as f32 * -TILE_SIZE - (rect_size.y - TILE_SIZE) / 2.0,
+        );
+
+        let collider_entity = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(center.extend(0.0))),
+            Collider {
+                width: rect_size.x,
+                height: rect_size.y,
+                membership: layers,
+                // Tiles never move, so are never stopped by anything
+                filter: CollisionLayers::empty(),
+                is_trigger: false,
+                one_way: false,
+            },
+        ));
+
+        tile_entities.push(collider_entity.id());
+    }
+
+    // Goals placed as objects have no sprite of their own
+    for goal in &level.goals {
+        let mut goal_entity = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                goal.x as f32 * TILE_SIZE,
+                goal.y as f32 * -TILE_SIZE,
+                0.0,
+            )),
+            Collider {
+                width: GOAL_COLLIDER_SIZE,
+                height: GOAL_COLLIDER_SIZE,
+                membership: CollisionLayers::NEUTRAL,
+                filter: CollisionLayers::empty(),
+                is_trigger: true,
+                one_way: false,
+            },
+            Goal,
+        ));
+
+        if let Some(link) = level.links.get(goal) {
+            goal_entity.insert((Lock { id: link.id }, Locked));
+            goal_locks.push((
+                Vec2::new(goal.x as f32 * TILE_SIZE, goal.y as f32 * -TILE_SIZE),
+                link.id,
+            ));
+        }
+
+        tile_entities.push(goal_entity.id());
+    }
+
+    // Locked doors are solid to both colors, opening like any linked block
+    for (position, id) in goal_locks {
+        let lock_block = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Collider {
+                width: TILE_SIZE,
+                height: TILE_SIZE,
+                membership: CollisionLayers::NEUTRAL,
+                filter: CollisionLayers::empty(),
+                is_trigger: false,
+                one_way: false,
+            },
+            LinkedBlock {
+                id,
+                inverted: false,
+                layers: CollisionLayers::NEUTRAL,
+            },
+        ));
+
+        tile_entities.push(lock_block.id());
+    }
+
+    // Same for hazards and checkpoints, which can cover any number of cells
+    for (cell, &kind) in &level.triggers {
+        let trigger_size = match kind {
+            TriggerKind::Hazard => HAZARD_COLLIDER_SIZE,
+            TriggerKind::Checkpoint => TILE_SIZE,
+        };
+
+        let mut trigger_entity = commands.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(
+                cell.x as f32 * TILE_SIZE,
+                cell.y as f32 * -TILE_SIZE,
+                0.0,
+            )),
+            Collider {
+                width: trigger_size,
+                height: trigger_size,
+                membership: CollisionLayers::NEUTRAL,
+                filter: CollisionLayers::empty(),
+                is_trigger: true,
+                one_way: false,
+            },
+        ));
+
+        match kind {
+            TriggerKind::Hazard => trigger_entity.insert(Hazard),
+            TriggerKind::Checkpoint => trigger_entity.insert(Checkpoint),
+        };
+
+        tile_entities.push(trigger_entity.id());
+    }
+
+    // Kept out of the tilemap, as moving colliders work in world space
+    for platform in &level.platforms {
+        spawn_platform(commands, &texture, &texture_atlas_layout, tileset, platform);
+    }
+
     commands
         .entity(tilemap_entity)
         .push_children(&tile_entities);
 }
+
+// One-way so rising platforms lift the player rather than hitting them from below
+fn spawn_platform(
+    commands: &mut Commands,
+    texture: &Handle<Image>,
+    texture_atlas_layout: &Handle<TextureAtlasLayout>,
+    tileset: &TilesetAsset,
+    platform: &PlatformPath,
+) {
+    let tiles = tileset.platforms[&platform.layers];
+    let width = platform.width as f32 * TILE_SIZE;
+    // Waypoints are the leftmost cell, the platform is positioned by its center
+    let waypoints = platform
+        .waypoints
+        .iter()
+        .map(|cell| {
+            Vec2::new(
+                cell.x as f32 * TILE_SIZE + (width - TILE_SIZE) / 2.0,
+                cell.y as f32 * -TILE_SIZE,
+            )
+        })
+        .collect::<Vec<Vec2>>();
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                waypoints[0].extend(TILEMAP_Z + COLLISION_Z),
+            )),
+            Collider {
+                width,
+                height: TILE_SIZE,
+                membership: platform.layers,
+                filter: CollisionLayers::empty(),
+                is_trigger: false,
+                one_way: true,
+            },
+            MovingCollider::default(),
+            MovingPlatform {
+                waypoints,
+                speed: platform.speed * TILE_SIZE,
+                next: 0,
+            },
+            Despawnable::with_children(true),
+        ))
+        .with_children(|parent| {
+            for i in 0..platform.width {
+                parent.spawn((
+                    SpriteBundle {
+                        transform: Transform::from_xyz(
+                            i as f32 * TILE_SIZE - (width - TILE_SIZE) / 2.0,
+                            0.0,
+                            0.0,
+                        )
+                        .with_scale(Vec3::splat(8.0 / 9.0)),
+                        texture: texture.clone_weak(),
+                        ..default()
+                    },
+                    TextureAtlas {
+                        layout: texture_atlas_layout.clone_weak(),
+                        index: tiles.get(i, platform.width),
+                    },
+                ));
+            }
+        });
+}
@@ -0,0 +1,414 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::level_asset::{LayerKind, LevelAsset, Link, PlatformPath, TileLayer, TriggerKind};
+use crate::{collision::CollisionLayers, player::PlayerType};
+
+// Upper bits of a gid hold the flip/rotate flags
+const GID_MASK: u32 = 0x1FFF_FFFF;
+
+// Subset of Tiled's JSON map format (.tmj) that we read
+#[derive(Deserialize)]
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    layers: Vec<TiledLayer>,
+    tilesets: Vec<TiledTileset>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TiledLayer {
+    TileLayer {
+        name: String,
+        #[serde(default)]
+        data: Vec<u32>,
+    },
+    ObjectGroup {
+        #[serde(default)]
+        objects: Vec<TiledObject>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct TiledObject {
+    #[serde(default, alias = "class")]
+    r#type: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+    // Relative to x and y
+    #[serde(default)]
+    polyline: Vec<TiledPoint>,
+}
+
+impl TiledObject {
+    fn property(&self, name: &str) -> Option<&serde_json::Value> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| &property.value)
+    }
+}
+
+#[derive(Deserialize)]
+struct TiledPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct TiledTileset {
+    firstgid: u32,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+    id: usize,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+struct TiledProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Debug, Error)]
+pub enum TiledMapLoaderError {
+    #[error("Could not read Tiled map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse Tiled map JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Tiled map has no tileset")]
+    NoTileset,
+    #[error("Tile layer has {0} cells, expected {1}")]
+    LayerSize(usize, usize),
+    #[error("Unknown layer `{0}` on tile {1}")]
+    UnknownLayer(String, usize),
+    #[error("Unknown layer `{0}` on platform")]
+    UnknownPlatformLayer(String),
+    #[error("Object at ({0}, {1}) is left of or above the map")]
+    OutsideMap(f32, f32),
+}
+
+#[derive(Default)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = TiledMapLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            to_level(serde_json::from_slice(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmj"]
+    }
+}
+
+fn to_level(map: TiledMap) -> Result<LevelAsset, TiledMapLoaderError> {
+    // Only the one atlas is supported, so gids map straight onto its indices
+    let tileset = map
+        .tilesets
+        .iter()
+        .min_by_key(|tileset| tileset.firstgid)
+        .ok_or(TiledMapLoaderError::NoTileset)?;
+
+    let mut layers = vec![];
+    let mut spawns = vec![];
+    let mut goals = vec![];
+    let mut platforms = vec![];
+    let mut links = HashMap::new();
+    let mut triggers = HashMap::new();
+
+    for layer in &map.layers {
+        match layer {
+            TiledLayer::TileLayer { name, data } => {
+                if data.len() != map.width * map.height {
+                    return Err(TiledMapLoaderError::LayerSize(
+                        data.len(),
+                        map.width * map.height,
+                    ));
+                }
+
+                let mut rows = vec![vec![None; map.width]; map.height];
+                for (i, &gid) in data.iter().enumerate() {
+                    let gid = gid & GID_MASK;
+                    if gid >= tileset.firstgid {
+                        rows[i / map.width][i % map.width] =
+                            Some((gid - tileset.firstgid) as usize);
+                    }
+                }
+
+                // Layers are named after their kind, e.g. "Background" or "Foreground trees"
+                layers.push(TileLayer {
+                    name: name.clone(),
+                    kind: LayerKind::from_layer_name(name),
+                    rows,
+                });
+            }
+            TiledLayer::ObjectGroup { objects } => {
+                for object in objects {
+                    let cell = cell_at(
+                        &map,
+                        object.x + object.width / 2.0,
+                        object.y + object.height / 2.0,
+                    )?;
+
+                    match object.r#type.as_str() {
+                        "spawn" => {
+                            let spawn_type = object
+                                .property("player_type")
+                                .and_then(|value| value.as_str())
+                                .and_then(PlayerType::from_name)
+                                .unwrap_or_default();
+                            spawns.push((cell, spawn_type));
+                        }
+                        "goal" => goals.push(cell),
+                        "platform" => platforms.push(to_platform(&map, object, cell)?),
+                        // Links every cell the object covers
+                        "link" => {
+                            let link = Link {
+                                id: object
+                                    .property("id")
+                                    .and_then(|value| value.as_u64())
+                                    .unwrap_or_default() as u32,
+                                inverted: object
+                                    .property("inverted")
+                                    .and_then(|value| value.as_bool())
+                                    .unwrap_or_default(),
+                                layers: object
+                                    .property("layer")
+                                    .and_then(|value| value.as_str())
+                                    .and_then(|name| {
+                                        CollisionLayers::from_name(&name.to_uppercase())
+                                    }),
+                            };
+                            for cell in covered_cells(&map, object)? {
+                                links.insert(cell, link);
+                            }
+                        }
+                        // Fill every cell the object covers, like links
+                        "hazard" => {
+                            for cell in covered_cells(&map, object)? {
+                                triggers.insert(cell, TriggerKind::Hazard);
+                            }
+                        }
+                        "checkpoint" => {
+                            for cell in covered_cells(&map, object)? {
+                                triggers.insert(cell, TriggerKind::Checkpoint);
+                            }
+                        }
+                        other => warn!("Skipping unknown Tiled object type `{}`", other),
+                    }
+                }
+            }
+            TiledLayer::Other => {}
+        }
+    }
+
+    let mut tile_layers = HashMap::new();
+    for tile in &tileset.tiles {
+        for property in &tile.properties {
+            if property.name != "layer" {
+                continue;
+            }
+
+            let Some(layers) = property
+                .value
+                .as_str()
+                .and_then(|name| CollisionLayers::from_name(&name.to_uppercase()))
+            else {
+                return Err(TiledMapLoaderError::UnknownLayer(
+                    property.value.to_string(),
+                    tile.id,
+                ));
+            };
+            tile_layers.insert(tile.id, layers);
+        }
+    }
+
+    let mut level = LevelAsset::new(layers);
+    level.goals = goals;
+    level.platforms = platforms;
+    level.links = links;
+    level.triggers = triggers;
+    level.tile_layers = tile_layers;
+    level.spawns = spawns;
+
+    Ok(level)
+}
+
+// Cells under the object's rectangle, or the one cell a point object sits in
+fn covered_cells(
+    map: &TiledMap,
+    object: &TiledObject,
+) -> Result<impl Iterator<Item = UVec2>, TiledMapLoaderError> {
+    let min = cell_at(map, object.x, object.y)?;
+    let max = UVec2::new(
+        ((object.x + object.width) / map.tilewidth).ceil() as u32,
+        ((object.y + object.height) / map.tileheight).ceil() as u32,
+    )
+    .max(min + UVec2::ONE);
+
+    Ok((min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| UVec2::new(x, y))))
+}
+
+// Tiled lets objects sit left of or above the map, where there is no cell for them
+fn cell_at(map: &TiledMap, x: f32, y: f32) -> Result<UVec2, TiledMapLoaderError> {
+    let cell = IVec2::new(
+        (x / map.tilewidth).floor() as i32,
+        (y / map.tileheight).floor() as i32,
+    );
+
+    if cell.cmplt(IVec2::ZERO).any() {
+        return Err(TiledMapLoaderError::OutsideMap(x, y));
+    }
+    Ok(cell.as_uvec2())
+}
+
+// The path is a polyline whose points are the waypoints, a plain object stays put
+fn to_platform(
+    map: &TiledMap,
+    object: &TiledObject,
+    cell: UVec2,
+) -> Result<PlatformPath, TiledMapLoaderError> {
+    let waypoints = if object.polyline.is_empty() {
+        vec![cell]
+    } else {
+        object
+            .polyline
+            .iter()
+            .map(|point| cell_at(map, object.x + point.x, object.y + point.y))
+            .collect::<Result<Vec<UVec2>, _>>()?
+    };
+
+    let mut platform = PlatformPath::new(waypoints);
+
+    if let Some(value) = object.property("layer") {
+        platform.layers = value
+            .as_str()
+            .and_then(|name| CollisionLayers::from_name(&name.to_uppercase()))
+            .ok_or_else(|| TiledMapLoaderError::UnknownPlatformLayer(value.to_string()))?;
+    }
+    if let Some(width) = object.property("width").and_then(|value| value.as_u64()) {
+        platform.width = width as u32;
+    }
+    if let Some(speed) = object.property("speed").and_then(|value| value.as_f64()) {
+        platform.speed = speed as f32;
+    }
+
+    Ok(platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_level() -> LevelAsset {
+        let bytes = include_bytes!("../../assets/levels/samples/level0.tmj");
+        to_level(serde_json::from_slice(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reads_tile_layer() {
+        let level = sample_level();
+
+        assert_eq!(level.size(), UVec2::new(20, 9));
+        assert_eq!(level.layers.len(), 1);
+
+        let layer = &level.layers[0];
+        assert_eq!(layer.name, "Tiles");
+        assert_eq!(layer.kind, LayerKind::Collision);
+        // Gids start at 1, atlas indices at 0, and 0 is an empty cell
+        assert_eq!(layer.rows[0][0], Some(21));
+        assert_eq!(layer.rows[0][3], None);
+        assert_eq!(layer.rows[4][13], Some(93));
+        assert_eq!(layer.rows[5][11], Some(9));
+        assert_eq!(layer.rows[8][19], Some(143));
+    }
+
+    #[test]
+    fn reads_spawn_and_goal_objects() {
+        let level = sample_level();
+
+        assert_eq!(level.spawns, vec![(UVec2::new(4, 3), PlayerType::Blue)]);
+        assert_eq!(level.goals, vec![UVec2::new(16, 5)]);
+    }
+
+    #[test]
+    fn reads_trigger_objects() {
+        let level = sample_level();
+
+        assert_eq!(level.triggers.len(), 3);
+        assert_eq!(
+            level.triggers.get(&UVec2::new(6, 5)),
+            Some(&TriggerKind::Hazard)
+        );
+        assert_eq!(
+            level.triggers.get(&UVec2::new(7, 5)),
+            Some(&TriggerKind::Hazard)
+        );
+        assert_eq!(
+            level.triggers.get(&UVec2::new(9, 5)),
+            Some(&TriggerKind::Checkpoint)
+        );
+    }
+
+    #[test]
+    fn reads_layer_properties() {
+        let level = sample_level();
+
+        assert_eq!(level.tile_layers.len(), 11);
+        assert_eq!(level.tile_layers.get(&9), Some(&CollisionLayers::YELLOW));
+        for index in [93, 94, 95, 113, 114, 115, 132, 133, 134, 135] {
+            assert_eq!(level.tile_layers.get(&index), Some(&CollisionLayers::BLUE));
+        }
+        assert_eq!(level.tile_layers.get(&21), None);
+    }
+
+    #[test]
+    fn rejects_objects_left_of_the_map() {
+        let mut map: TiledMap =
+            serde_json::from_slice(include_bytes!("../../assets/levels/samples/level0.tmj"))
+                .unwrap();
+        let TiledLayer::ObjectGroup { objects } = &mut map.layers[1] else {
+            panic!("Expected the sample's object layer second");
+        };
+        objects[0].x = -16.0;
+
+        assert!(matches!(
+            to_level(map),
+            Err(TiledMapLoaderError::OutsideMap(x, _)) if x == -8.0
+        ));
+    }
+}
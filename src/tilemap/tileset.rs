@@ -0,0 +1,127 @@
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::collision::CollisionLayers;
+
+// Per atlas index properties, anything not listed falls back to `default`
+// Without a `default`, tiles that aren't listed are unknown
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct TilesetAsset {
+    pub image: String,
+    pub tile_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    #[serde(default)]
+    pub default: Option<TileProperties>,
+    pub tiles: HashMap<usize, TileProperties>,
+    // Moving platform pieces for each collision layer a platform can be on
+    #[serde(default)]
+    pub platforms: HashMap<CollisionLayers, PlatformTiles>,
+}
+
+impl TilesetAsset {
+    pub fn get(&self, index: usize) -> Option<&TileProperties> {
+        self.tiles.get(&index).or(self.default.as_ref())
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.columns * self.rows
+    }
+}
+
+// Atlas indices for the ends and the rest of a moving platform
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct PlatformTiles {
+    pub left: usize,
+    pub middle: usize,
+    pub right: usize,
+}
+
+impl PlatformTiles {
+    // One cell wide platforms only get the middle piece
+    pub fn get(&self, i: u32, width: u32) -> usize {
+        match i {
+            _ if width == 1 => self.middle,
+            0 => self.left,
+            i if i == width - 1 => self.right,
+            _ => self.middle,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TileProperties {
+    pub layers: CollisionLayers,
+    pub solid: bool,
+    // Solid only to bodies landing on it from above
+    pub one_way: bool,
+    pub trigger: bool,
+    pub goal: bool,
+    // Kills the player on touch, needs `trigger` too
+    pub hazard: bool,
+    // Respawn here after touching it, needs `trigger` too
+    pub checkpoint: bool,
+    // Turns its link id on while touched, needs `trigger` too
+    pub plate: bool,
+    // Flips its link id on each touch, needs `trigger` too
+    pub lever: bool,
+    // Picked up on touch, turning its link id on, needs `trigger` too
+    pub key: bool,
+}
+
+impl Default for TileProperties {
+    fn default() -> Self {
+        TileProperties {
+            layers: CollisionLayers::NEUTRAL,
+            solid: true,
+            one_way: false,
+            trigger: false,
+            goal: false,
+            hazard: false,
+            checkpoint: false,
+            plate: false,
+            lever: false,
+            key: false,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TilesetAssetLoaderError {
+    #[error("Could not read tileset file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse tileset RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct TilesetAssetLoader;
+
+impl AssetLoader for TilesetAssetLoader {
+    type Asset = TilesetAsset;
+    type Settings = ();
+    type Error = TilesetAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<TilesetAsset>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tileset.ron"]
+    }
+}
@@ -0,0 +1,368 @@
+use bevy::{prelude::*, utils::HashSet};
+use thiserror::Error;
+
+use super::{
+    level_asset::{LayerKind, LevelAsset, TriggerKind},
+    tileset::TilesetAsset,
+};
+use crate::collision::CollisionLayers;
+
+// Rows and columns are 1-based to match what editors show
+#[derive(Debug, Error)]
+pub enum LevelError {
+    #[error("Level is empty")]
+    Empty,
+    #[error("Layer `{layer}` has {height} rows, expected {expected}")]
+    LayerHeight {
+        layer: String,
+        height: usize,
+        expected: usize,
+    },
+    #[error("Layer `{layer}`, row {row} has {width} cells, expected {expected}")]
+    RowWidth {
+        layer: String,
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    #[error("Layer `{layer}`, row {row}, column {column}: `{text}` is not a tile index or marker")]
+    InvalidCell {
+        layer: String,
+        row: usize,
+        column: usize,
+        text: String,
+    },
+    #[error("Layer `{layer}`, row {row}, column {column}: tile {index} is outside the {tile_count} tile atlas")]
+    OutOfAtlas {
+        layer: String,
+        row: usize,
+        column: usize,
+        index: usize,
+        tile_count: usize,
+    },
+    #[error("Layer `{layer}`, row {row}, column {column}: tile {index} has no tileset properties")]
+    UnknownTile {
+        layer: String,
+        row: usize,
+        column: usize,
+        index: usize,
+    },
+    #[error(
+        "Layer `{layer}`, row {row}, column {column}: lever, pressure plate or key has no link id"
+    )]
+    UnlinkedTile {
+        layer: String,
+        row: usize,
+        column: usize,
+    },
+    #[error("{object} at row {row}, column {column} is outside the level")]
+    ObjectOutOfBounds {
+        object: &'static str,
+        row: usize,
+        column: usize,
+    },
+    #[error("Expected exactly one spawn, found {0}")]
+    SpawnCount(usize),
+    #[error("Expected exactly one goal door, found {0}")]
+    GoalCount(usize),
+    #[error("Platform {platform} is {width} cells wide, expected at least 1")]
+    PlatformWidth { platform: usize, width: u32 },
+    #[error(
+        "Platform {platform} is on layer {layers:?}, which the tileset has no platform tiles for"
+    )]
+    PlatformTiles {
+        platform: usize,
+        layers: CollisionLayers,
+    },
+    #[error("Platform {platform}, waypoint at row {row}, column {column} runs outside the level")]
+    PlatformWaypoint {
+        platform: usize,
+        row: usize,
+        column: usize,
+    },
+}
+
+fn position(cell: UVec2) -> (usize, usize) {
+    (cell.y as usize + 1, cell.x as usize + 1)
+}
+
+pub fn validate_level(level: &LevelAsset, tileset: &TilesetAsset) -> Vec<LevelError> {
+    let mut errors = vec![];
+
+    // Every layer has to line up with the first
+    let expected = level.size();
+    if expected.x == 0 || expected.y == 0 {
+        return vec![LevelError::Empty];
+    }
+
+    for layer in &level.layers {
+        if layer.rows.len() != expected.y as usize {
+            errors.push(LevelError::LayerHeight {
+                layer: layer.name.clone(),
+                height: layer.rows.len(),
+                expected: expected.y as usize,
+            });
+        }
+
+        for (y, row) in layer.rows.iter().enumerate() {
+            if row.len() != expected.x as usize {
+                errors.push(LevelError::RowWidth {
+                    layer: layer.name.clone(),
+                    row: y + 1,
+                    width: row.len(),
+                    expected: expected.x as usize,
+                });
+            }
+        }
+    }
+
+    for (layer, cell, text) in &level.invalid_cells {
+        let (row, column) = position(*cell);
+        errors.push(LevelError::InvalidCell {
+            layer: layer.clone(),
+            row,
+            column,
+            text: text.clone(),
+        });
+    }
+
+    let mut goal_cells = level.goals.iter().copied().collect::<HashSet<UVec2>>();
+
+    for layer in &level.layers {
+        for (y, row) in layer.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let Some(index) = *cell else {
+                    continue;
+                };
+                let (row, column) = (y + 1, x + 1);
+
+                if index >= tileset.tile_count() {
+                    errors.push(LevelError::OutOfAtlas {
+                        layer: layer.name.clone(),
+                        row,
+                        column,
+                        index,
+                        tile_count: tileset.tile_count(),
+                    });
+                    continue;
+                }
+
+                let cell = UVec2::new(x as u32, y as u32);
+                match tileset.get(index) {
+                    // Goals only count on layers that collide
+                    Some(properties) if properties.goal && layer.kind == LayerKind::Collision => {
+                        goal_cells.insert(cell);
+                    }
+                    Some(properties)
+                        if (properties.plate || properties.lever || properties.key)
+                            && layer.kind == LayerKind::Collision
+                            && !level.links.contains_key(&cell) =>
+                    {
+                        errors.push(LevelError::UnlinkedTile {
+                            layer: layer.name.clone(),
+                            row,
+                            column,
+                        });
+                    }
+                    Some(_) => {}
+                    None => errors.push(LevelError::UnknownTile {
+                        layer: layer.name.clone(),
+                        row,
+                        column,
+                        index,
+                    }),
+                }
+            }
+        }
+    }
+
+    // Tiles are always inside, but objects from Tiled or LDtk can be placed anywhere
+    let objects = level
+        .spawns
+        .iter()
+        .map(|&(cell, _)| ("Spawn", cell))
+        .chain(level.goals.iter().map(|&cell| ("Goal", cell)))
+        .chain(level.triggers.iter().map(|(&cell, kind)| match kind {
+            TriggerKind::Hazard => ("Hazard", cell),
+            TriggerKind::Checkpoint => ("Checkpoint", cell),
+        }));
+    for (object, cell) in objects {
+        if cell.x >= expected.x || cell.y >= expected.y {
+            let (row, column) = position(cell);
+            errors.push(LevelError::ObjectOutOfBounds {
+                object,
+                row,
+                column,
+            });
+        }
+    }
+
+    if level.spawns.len() != 1 {
+        errors.push(LevelError::SpawnCount(level.spawns.len()));
+    }
+
+    // A door spans several cells, so count touching goal cells once
+    let goal_count = count_groups(&goal_cells);
+    if goal_count != 1 {
+        errors.push(LevelError::GoalCount(goal_count));
+    }
+
+    // Numbered from 1 in file order
+    for (i, platform) in level.platforms.iter().enumerate() {
+        if platform.width == 0 {
+            errors.push(LevelError::PlatformWidth {
+                platform: i + 1,
+                width: platform.width,
+            });
+        }
+
+        if !tileset.platforms.contains_key(&platform.layers) {
+            errors.push(LevelError::PlatformTiles {
+                platform: i + 1,
+                layers: platform.layers,
+            });
+        }
+
+        for &waypoint in &platform.waypoints {
+            if waypoint.x + platform.width > expected.x || waypoint.y >= expected.y {
+                let (row, column) = position(waypoint);
+                errors.push(LevelError::PlatformWaypoint {
+                    platform: i + 1,
+                    row,
+                    column,
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+fn count_groups(cells: &HashSet<UVec2>) -> usize {
+    let mut visited = HashSet::new();
+    let mut groups = 0;
+
+    for &start in cells {
+        if !visited.insert(start) {
+            continue;
+        }
+        groups += 1;
+
+        let mut stack = vec![start];
+        while let Some(cell) = stack.pop() {
+            let neighbours = [
+                cell + UVec2::X,
+                cell + UVec2::Y,
+                cell.saturating_sub(UVec2::X),
+                cell.saturating_sub(UVec2::Y),
+            ];
+
+            for neighbour in neighbours {
+                if cells.contains(&neighbour) && visited.insert(neighbour) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::ron;
+
+    use super::*;
+    use crate::{player::PlayerType, tilemap::level_asset::PlatformPath};
+
+    fn tileset() -> TilesetAsset {
+        ron::de::from_bytes(include_bytes!("../../assets/tilemap_packed.tileset.ron")).unwrap()
+    }
+
+    #[test]
+    fn accepts_objects_inside_the_level() {
+        let mut level = LevelAsset::parse("x, x, 110\nx, x, 130").unwrap();
+        level.spawns.push((UVec2::new(0, 1), PlayerType::Blue));
+        level.triggers.insert(UVec2::new(1, 1), TriggerKind::Hazard);
+
+        assert!(validate_level(&level, &tileset()).is_empty());
+    }
+
+    #[test]
+    fn rejects_objects_outside_the_level() {
+        let mut level = LevelAsset::parse("S, x, 110\nx, x, 130").unwrap();
+        level.spawns.push((UVec2::new(3, 0), PlayerType::Blue));
+        level.goals.push(UVec2::new(0, 2));
+        level
+            .triggers
+            .insert(UVec2::new(5, 5), TriggerKind::Checkpoint);
+
+        let mut outside = validate_level(&level, &tileset())
+            .into_iter()
+            .filter_map(|error| match error {
+                LevelError::ObjectOutOfBounds {
+                    object,
+                    row,
+                    column,
+                } => Some((object, row, column)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        outside.sort();
+
+        assert_eq!(
+            outside,
+            vec![("Checkpoint", 6, 6), ("Goal", 3, 1), ("Spawn", 1, 4)]
+        );
+    }
+
+    #[test]
+    fn needs_platform_tiles_for_the_platform_layer() {
+        let mut level = LevelAsset::parse("S, x, 110\nx, x, 130").unwrap();
+        let mut platform = PlatformPath::new(vec![UVec2::ZERO]);
+        platform.width = 1;
+        platform.layers = CollisionLayers::BLUE;
+        level.platforms.push(platform.clone());
+        platform.layers = CollisionLayers::BLUE | CollisionLayers::YELLOW;
+        level.platforms.push(platform);
+
+        let errors = validate_level(&level, &tileset());
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            LevelError::PlatformTiles { platform: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn shipped_levels_only_use_known_tiles() {
+        let levels = [
+            include_str!("../../assets/levels/level0.csv"),
+            include_str!("../../assets/levels/level1.csv"),
+            include_str!("../../assets/levels/level2.csv"),
+        ];
+
+        for level in levels {
+            let level = LevelAsset::parse(level).unwrap();
+            assert!(validate_level(&level, &tileset()).is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_tiles_missing_from_the_tileset() {
+        // 24 is inside the atlas but not listed
+        let level = LevelAsset::parse("S, 24, 110\nx, x, 130").unwrap();
+
+        let errors = validate_level(&level, &tileset());
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            LevelError::UnknownTile {
+                row: 1,
+                column: 2,
+                index: 24,
+                ..
+            }
+        ));
+    }
+}
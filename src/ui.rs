@@ -1,12 +1,34 @@
 use bevy::prelude::*;
 
-use crate::level::{Despawnable, LevelIndex, LevelState};
+use crate::{
+    level::{CollectedKeys, DeathCount, Despawnable, LevelErrors, LevelIndex, LevelState},
+    links::key_color,
+    tilemap::Tilemap,
+};
+
+const KEY_ICON_SIZE: f32 = 36.0;
+
+#[derive(Component)]
+struct ErrorScreen;
+
+#[derive(Component)]
+struct DeathCounter;
+
+// Row of collected key icons
+#[derive(Component)]
+struct KeyBar;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(LevelState::Init), setup);
+        app.add_systems(
+            OnEnter(LevelState::Init),
+            (setup, spawn_death_counter, spawn_key_bar),
+        )
+        .add_systems(Update, (update_death_counter, update_key_bar))
+        .add_systems(OnEnter(LevelState::Error), show_level_errors)
+        .add_systems(OnExit(LevelState::Error), hide_level_errors);
     }
 }
 
@@ -18,7 +40,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, level_index: Re
                 NodeBundle {
                     style: Style {
                         width: Val::Percent(100.0),
-                        height: Val::Px(120.),
+                        height: Val::Px(160.),
                         justify_content: JustifyContent::Center,
                         ..default()
                     },
@@ -31,11 +53,10 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, level_index: Re
             .with_children(|parent| {
                 // Add text to screen
                 parent.spawn(TextBundle::from_section(
-                    "Arrow Keys to move, Space to Jump\nLeft Shift to Change Characters, R to Restart", TextStyle {
+                    "Arrow Keys to move, Space to Jump\nLeft Shift to Change Characters, R to Restart\nC to go back to the last Checkpoint", TextStyle {
                         font: asset_server.load("Pixellari.ttf"),
                         font_size: 36.0,
                         color: Color::WHITE,
-                        ..default()
                     })
                     .with_text_justify(JustifyText::Center)
                     .with_style(Style {
@@ -46,3 +67,166 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, level_index: Re
             });
     }
 }
+
+fn spawn_death_counter(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    death_count: Res<DeathCount>,
+) {
+    // Under the Fader like the level
+    commands.spawn((
+        TextBundle {
+            z_index: ZIndex::Global(-1),
+            ..TextBundle::from_section(
+                death_counter_text(death_count.0),
+                TextStyle {
+                    font: asset_server.load("Pixellari.ttf"),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.),
+                right: Val::Px(16.),
+                ..default()
+            })
+        },
+        DeathCounter,
+        Despawnable::default(),
+    ));
+}
+
+fn update_death_counter(
+    death_count: Res<DeathCount>,
+    mut counter_query: Query<&mut Text, With<DeathCounter>>,
+) {
+    if !death_count.is_changed() {
+        return;
+    }
+
+    for mut text in &mut counter_query {
+        text.sections[0].value = death_counter_text(death_count.0);
+    }
+}
+
+fn death_counter_text(deaths: u32) -> String {
+    format!("Deaths: {}", deaths)
+}
+
+fn spawn_key_bar(mut commands: Commands) {
+    // Under the Fader like the level
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.),
+                left: Val::Px(16.),
+                column_gap: Val::Px(4.),
+                ..default()
+            },
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        KeyBar,
+        Despawnable::with_children(true),
+    ));
+}
+
+// Icons come from the tilemap's atlas, so are redrawn when it is rebuilt too
+fn update_key_bar(
+    mut commands: Commands,
+    collected_keys: Res<CollectedKeys>,
+    key_bar_query: Query<Entity, With<KeyBar>>,
+    tilemap_query: Query<(Ref<Tilemap>, &Handle<Image>, &Handle<TextureAtlasLayout>)>,
+) {
+    let (Ok(key_bar), Ok((tilemap, texture, layout))) =
+        (key_bar_query.get_single(), tilemap_query.get_single())
+    else {
+        return;
+    };
+
+    if !collected_keys.is_changed() && !tilemap.is_added() {
+        return;
+    }
+
+    let mut key_bar = commands.entity(key_bar);
+    key_bar.despawn_descendants();
+    key_bar.with_children(|parent| {
+        for key in &collected_keys.0 {
+            parent.spawn(AtlasImageBundle {
+                style: Style {
+                    width: Val::Px(KEY_ICON_SIZE),
+                    height: Val::Px(KEY_ICON_SIZE),
+                    ..default()
+                },
+                image: UiImage::new(texture.clone_weak()),
+                // Tints the image
+                background_color: BackgroundColor(key_color(key.layers)),
+                texture_atlas: TextureAtlas {
+                    layout: layout.clone_weak(),
+                    index: key.index,
+                },
+                ..default()
+            });
+        }
+    });
+}
+
+fn show_level_errors(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_errors: Res<LevelErrors>,
+) {
+    for error in &level_errors.0 {
+        error!("{}", error);
+    }
+
+    let font = asset_server.load("Pixellari.ttf");
+
+    // Drawn over the Fader and anything left of the level
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(24.)),
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::BLACK),
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            ErrorScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Could not load levels",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 36.0,
+                    color: Color::RED,
+                },
+            ));
+
+            for error in &level_errors.0 {
+                parent.spawn(TextBundle::from_section(
+                    error,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            }
+        });
+}
+
+fn hide_level_errors(mut commands: Commands, error_screen_query: Query<Entity, With<ErrorScreen>>) {
+    for entity in &error_screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}